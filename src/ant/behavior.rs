@@ -1,5 +1,6 @@
 use crate::{
 	ant::{
+		input_plan::InputPlan,
 		pin::{IoType, Pin},
 		sub_pin::SubPin,
 	},
@@ -7,6 +8,8 @@ use crate::{
 	util::find_dupe,
 };
 
+use std::collections::BTreeSet;
+
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 #[cfg_attr(test, derive(ts_rs::TS))]
@@ -17,6 +20,9 @@ pub struct Behavior {
 	pub logic: TruthTable,
 	pub inputs: Vec<SubPin>,
 	pub outputs: Vec<SubPin>,
+	/// compiled from `inputs`
+	#[cfg_attr(test, ts(skip))]
+	pub input_plan: InputPlan,
 }
 
 #[cfg_attr(test, derive(ts_rs::TS))]
@@ -75,11 +81,14 @@ impl Behavior {
 		Self::validate_pins(&inputs, IoType::Input)?;
 		Self::validate_pins(&outputs, IoType::Output)?;
 
+		let input_plan = InputPlan::new(&inputs);
+
 		Ok(Self {
 			logic: truth_table,
 			name,
 			inputs,
 			outputs,
+			input_plan,
 		})
 	}
 
@@ -95,11 +104,33 @@ impl Behavior {
 		}
 	}
 
-	pub fn pin_mask(&self, pin: Pin) -> u8 {
+	/// the values the behavior may write to the pin, over all inputs
+	pub fn output_values(&self, pin: Pin) -> BTreeSet<u16> {
+		(0..self.logic.entries().len())
+			.map(|input| {
+				let mut output_bits = self.logic.get(input as u8);
+				let mut value = 0;
+
+				// same order as the output bits are condensed in
+				for sub_pin in self.outputs.iter().rev() {
+					if sub_pin.pin == pin {
+						value |=
+							((output_bits & 1) as u16) << ((sub_pin.channel << 3) | sub_pin.line);
+					}
+
+					output_bits >>= 1;
+				}
+
+				value
+			})
+			.collect()
+	}
+
+	pub fn pin_mask(&self, pin: Pin, plane: Option<u8>) -> u8 {
 		let mut mask = 0;
 
 		for sub_pin in &self.outputs {
-			if pin == sub_pin.pin {
+			if pin == sub_pin.pin && plane == sub_pin.plane {
 				mask |= 1 << sub_pin.line;
 			}
 		}
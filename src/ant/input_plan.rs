@@ -0,0 +1,73 @@
+use crate::ant::{pin::Pin, sub_pin::SubPin};
+
+/// a source value that is fetched once per tick, and the bits to extract from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputFetch {
+	pub pin: Pin,
+	pub channel: u8,
+	pub plane: Option<u8>,
+	pub layer: Option<u8>,
+	/// `(line, shift)`: bit `line` of the fetched value goes to bit `shift` of the input byte
+	pub bits: Vec<(u8, u8)>,
+}
+
+impl InputFetch {
+	#[inline]
+	pub fn extract(&self, value: u8) -> u8 {
+		self.bits
+			.iter()
+			.map(|&(line, shift)| ((value >> line) & 1) << shift)
+			.fold(0, |input_bits, bit| input_bits | bit)
+	}
+}
+
+/// the inputs of a Behavior, grouped by (pin, channel, plane, layer)
+/// so that every source value only needs to be fetched once
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputPlan(Vec<InputFetch>);
+
+impl InputPlan {
+	pub fn new(inputs: &[SubPin]) -> Self {
+		let mut fetches: Vec<InputFetch> = vec![];
+
+		for (i, sub_pin) in inputs.iter().enumerate() {
+			let SubPin {
+				pin,
+				line,
+				channel,
+				plane,
+				layer,
+			} = *sub_pin;
+
+			// the first input ends up as the most significant bit
+			let shift = (inputs.len() - 1 - i) as u8;
+
+			// random pins draw a new value for every bit,
+			// so that bits stay independent and the order of draws is preserved
+			let shared = !matches!(pin, Pin::Random | Pin::Chance);
+
+			if shared
+				&& let Some(fetch) = fetches.iter_mut().find(|fetch| {
+					(fetch.pin, fetch.channel, fetch.plane, fetch.layer)
+						== (pin, channel, plane, layer)
+				}) {
+				fetch.bits.push((line, shift));
+			} else {
+				fetches.push(InputFetch {
+					pin,
+					channel,
+					plane,
+					layer,
+					bits: vec![(line, shift)],
+				});
+			}
+		}
+
+		Self(fetches)
+	}
+
+	#[inline]
+	pub fn fetches(&self) -> &[InputFetch] {
+		&self.0
+	}
+}
@@ -1,27 +1,36 @@
+pub mod input_plan;
 pub mod pin;
 pub mod sub_pin;
 
 use std::ops::{Deref, DerefMut};
 
+use serde::{Deserialize, Serialize};
+
 use crate::util::{dir::Direction, hash_u32};
 
 pub mod behavior;
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct Ant {
 	pub behavior: u8,
 	pub birth_tick: u32,
+	/// unique within a run, starting at 1
+	pub serial: u32,
+	/// serial of the spawning ant, 0 for start ants
+	pub parent: u32,
 
 	pub clock: u8,
 	pub wait_ticks: u8,
 	pub dir: Direction,
 	pub memory: u8,
+	/// remaining energy, if the energy model is enabled
+	pub energy: u16,
 
-	// todo: exclude from serialization
+	/// carries over between ticks, so it is part of snapshots
 	pub data: TickData,
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct TickData {
 	pub last_input: u8,
 
@@ -52,6 +52,22 @@ pub enum Pin {
 	/// neighboring ant's Memory
 	NearbyMem,
 
+	// ## other layers
+	/// bits being true for each layer with an ant on the current position
+	LayerAnt,
+	/// ID of the ant on the current position of another layer
+	LayerId,
+	/// Memory of the ant on the current position of another layer
+	LayerMem,
+
+	// ## diffusion field
+	/// current tile's field value, adds to it when written
+	Field,
+	/// neighboring tile's field value
+	NearbyField,
+	/// true if neighboring tile's field value exceeds the current tile's
+	Gradient,
+
 	// ## generic inputs
 	/// is 1 on the birth tick (+1) of the ant, else 0
 	Init,
@@ -61,6 +77,8 @@ pub enum Pin {
 	Pulse,
 	/// current ant's persistent memory
 	Mem,
+	/// current ant's energy, capped at 255 (0 if the energy model is disabled)
+	Energy,
 	/// 8 random bits
 	Random,
 	/// random bits, where each value has
@@ -96,7 +114,7 @@ pub struct PinDefinition {
 }
 
 impl Pin {
-	const PIN_DEFINITIONS: [PinDefinition; 25] = [
+	const PIN_DEFINITIONS: [PinDefinition; 32] = [
 		PinDefinition {
 			pin: Self::SpawnId,
 			code: "A",
@@ -145,6 +163,12 @@ impl Pin {
 			size: BIT,
 			io_type: Some(IoType::Output),
 		},
+		PinDefinition {
+			pin: Self::Energy,
+			code: "E",
+			size: BYTE,
+			io_type: Some(IoType::Input),
+		},
 		PinDefinition {
 			pin: Self::Halt,
 			code: "H",
@@ -163,12 +187,36 @@ impl Pin {
 			size: BYTE,
 			io_type: Some(IoType::Input),
 		},
+		PinDefinition {
+			pin: Self::LayerAnt,
+			code: "L",
+			size: BYTE,
+			io_type: Some(IoType::Input),
+		},
+		PinDefinition {
+			pin: Self::LayerId,
+			code: "LA",
+			size: ANT_ID,
+			io_type: Some(IoType::Input),
+		},
+		PinDefinition {
+			pin: Self::LayerMem,
+			code: "LM",
+			size: BYTE,
+			io_type: Some(IoType::Input),
+		},
 		PinDefinition {
 			pin: Self::Mem,
 			code: "M",
 			size: BYTE,
 			io_type: None,
 		},
+		PinDefinition {
+			pin: Self::Field,
+			code: "P",
+			size: BYTE,
+			io_type: None,
+		},
 		PinDefinition {
 			pin: Self::Random,
 			code: "R",
@@ -217,12 +265,24 @@ impl Pin {
 			size: DOUBLE,
 			io_type: Some(IoType::Input),
 		},
+		PinDefinition {
+			pin: Self::Gradient,
+			code: "VG",
+			size: BYTE,
+			io_type: Some(IoType::Input),
+		},
 		PinDefinition {
 			pin: Self::NearbyMem,
 			code: "VM",
 			size: DOUBLE,
 			io_type: Some(IoType::Input),
 		},
+		PinDefinition {
+			pin: Self::NearbyField,
+			code: "VP",
+			size: DOUBLE,
+			io_type: Some(IoType::Input),
+		},
 		PinDefinition {
 			pin: Self::Wait,
 			code: "W",
@@ -271,24 +331,62 @@ impl Pin {
 	#[inline]
 	/// specifies that a pin needs the line bits to be the channel bits.
 	///
-	/// currently only used for special treatment of `NearbyAnt`.
+	/// used for the one-bit-per-channel pins `NearbyAnt` and `Gradient`.
 	pub fn prefers_channel(&self) -> bool {
-		matches!(self, Self::NearbyAnt)
+		matches!(self, Self::NearbyAnt | Self::Gradient)
+	}
+
+	#[inline]
+	/// pins that read or write a tile plane
+	pub fn has_planes(&self) -> bool {
+		matches!(self, Self::Tile | Self::Clear | Self::NearbyTile)
+	}
+
+	#[inline]
+	/// pins that sense a neighboring tile, with the channel as the direction relative to the ant
+	pub fn has_dirs(&self) -> bool {
+		matches!(
+			self,
+			Self::NearbyTile
+				| Self::NearbyAnt
+				| Self::NearbyId
+				| Self::NearbyMem
+				| Self::NearbyField
+				| Self::Gradient
+		)
+	}
+
+	#[inline]
+	/// pins that sense ants, either on the ant's own layer or on an explicitly addressed one
+	pub fn has_layers(&self) -> bool {
+		matches!(
+			self,
+			Self::NearbyAnt | Self::NearbyId | Self::NearbyMem | Self::LayerId | Self::LayerMem
+		)
 	}
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PinValue {
 	pub pin: Pin,
+	pub plane: Option<u8>,
 	pub value: u16,
 }
 
 #[cfg(test)]
 mod test {
-	use crate::ant::pin::IoType;
+	use crate::ant::{pin::IoType, sub_pin::SubPin};
 
 	use super::Pin;
 
+	#[test]
+	fn layer_pins_need_a_layer() {
+		assert!(SubPin::from_ident("LA").is_err());
+		assert!(SubPin::from_ident("LM3").is_err());
+		assert!(SubPin::from_ident("LA_1").is_ok());
+		assert!(SubPin::from_ident("LM3_0").is_ok());
+	}
+
 	#[test]
 	#[rustfmt::skip]
 	fn export_pin_definitions() {
@@ -10,6 +10,10 @@ pub struct SubPin {
 	pub pin: Pin,
 	pub line: u8,
 	pub channel: u8,
+	/// explicitly addressed tile plane, else the plane of the ant's layer
+	pub plane: Option<u8>,
+	/// explicitly addressed layer of sensed ants, else the ant's own layer
+	pub layer: Option<u8>,
 }
 
 impl Serialize for SubPin {
@@ -36,7 +40,18 @@ impl SubPin {
 		let mut ident = self.pin.short_ident().to_owned();
 
 		if self.pin.definition().size > 1 {
-			ident.push_str(&format!("{:x}", self.line));
+			// inverse of the index split in `from_ident`
+			let bit_index = if self.pin.prefers_channel() {
+				self.channel
+			} else {
+				(self.channel << 3) | self.line
+			};
+
+			ident.push_str(&format!("{bit_index:o}"));
+		}
+
+		if let Some(index) = self.plane.or(self.layer) {
+			ident.push_str(&format!("_{index}"));
 		}
 
 		ident
@@ -61,7 +76,7 @@ impl SubPin {
 		}
 	}
 
-	const PIN_PTN: &str = r"^([A-Z_]+)([0-7]{1,2})?$";
+	const PIN_PTN: &str = r"^([A-Z_]+?)([0-7]{1,2})?(?:_([0-7]))?$";
 
 	pub fn from_ident(ident: &str) -> Result<Self> {
 		// parsing...
@@ -78,6 +93,9 @@ impl SubPin {
 			.get(2)
 			.map(|m| u8::from_str_radix(m.as_str(), 8).unwrap());
 
+		// either a tile plane or a layer, depending on the pin
+		let suffix = captures.get(3).map(|m| m.as_str().parse::<u8>().unwrap());
+
 		// indexes...
 
 		let explicit_index = bit_index.is_some();
@@ -99,12 +117,42 @@ impl SubPin {
 
 		// validation ...
 
-		if explicit_index && size == 1 {
+		if suffix.is_some() && !pin.has_planes() && !pin.has_layers() {
+			bail!("only tile pins can address a tile plane, and ant pins a layer\n(in '{ident}')");
+		} else if suffix.is_none() && matches!(pin, Pin::LayerId | Pin::LayerMem) {
+			// on its own layer, the ant would only sense itself
+			bail!("layer pins need to address a layer, e.g. '{ident}_1'\n(in '{ident}')");
+		} else if explicit_index && size == 1 {
 			bail!("may not have an explicit bit index in one-bit pins\n(in '{ident}')");
 		} else if bit_index >= size {
 			bail!("bit index may not exceed pin bit cap:\n{bit_index} >= {size}\n(in '{ident}')");
 		}
 
-		Ok(Self { pin, line, channel })
+		let (plane, layer) = if pin.has_planes() {
+			(suffix, None)
+		} else {
+			(None, suffix)
+		};
+
+		Ok(Self {
+			pin,
+			line,
+			channel,
+			plane,
+			layer,
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::SubPin;
+
+	#[test]
+	fn ident_round_trip() {
+		// channels of wide pins, and of pins that only have channels
+		for ident in ["H", "M5", "D2", "V6", "VC47", "X73"] {
+			assert_eq!(SubPin::from_ident(ident).unwrap().to_ident(), ident);
+		}
 	}
 }
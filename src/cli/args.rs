@@ -1,6 +1,10 @@
 use crate::{
 	parser::{Parser, token::Token},
-	world::config::{RenderMask, WorldConfig},
+	world::{
+		config::{RenderMask, WorldConfig},
+		event_log::EventLog,
+		metrics::MetricsWriter,
+	},
 };
 
 use clap::{self, Parser as ClapParser};
@@ -44,6 +48,26 @@ pub struct Args {
 	#[arg(short, long)]
 	pub raw: bool,
 
+	/// Run without rendering until the simulation ends or is interrupted
+	#[arg(short = 'H', long)]
+	pub headless: bool,
+
+	/// Write per-tick metrics to a .csv or .jsonl file while running
+	#[arg(short, long)]
+	pub metrics: Option<PathBuf>,
+
+	/// Write ant events (births, deaths, kills, moves, blocked moves, lost conflicts) to a .jsonl file
+	#[arg(short, long)]
+	pub events: Option<PathBuf>,
+
+	/// Only log these event kinds (comma-separated, e.g. "death,kill")
+	#[arg(long, value_delimiter = ',')]
+	pub event_kinds: Vec<String>,
+
+	/// Only log events of ants with these behavior IDs (comma-separated)
+	#[arg(long, value_delimiter = ',')]
+	pub event_ids: Vec<u8>,
+
 	/// pass args to sub-process, e.g. a nodejs file
 	#[arg(short = 'a', long = "args")]
 	pub sub_args: Option<String>,
@@ -64,11 +88,44 @@ pub struct Args {
 	/// Show a preview of the dimensions of the antlet
 	#[arg(short, long)]
 	pub preview: bool,
+
+	/// Resume from a snapshot file at PATH instead of compiling a world
+	#[arg(short = 'R', long)]
+	pub resume: bool,
+
+	/// Save a snapshot of the world when the simulation stops
+	#[arg(short = 'o', long)]
+	pub save: Option<PathBuf>,
+
+	/// Record the seed and all key inputs to a replay file when the simulation stops
+	/// (replays always start from a compiled world, so not when resuming)
+	#[arg(long, conflicts_with = "resume")]
+	pub record: Option<PathBuf>,
+
+	/// Replay the seed and key inputs from a replay file
+	#[arg(long, conflicts_with = "resume")]
+	pub replay: Option<PathBuf>,
 }
 
 const MAX_TICKS: u32 = u16::MAX as u32;
 
 impl Args {
+	pub fn metrics_writer(&self) -> anyhow::Result<Option<MetricsWriter>> {
+		self.metrics
+			.as_deref()
+			.map(MetricsWriter::create)
+			.transpose()
+	}
+
+	pub fn event_log(&self) -> anyhow::Result<Option<EventLog>> {
+		let Some(path) = &self.events else {
+			return Ok(None);
+		};
+
+		let event_log = EventLog::create(path, &self.event_kinds, &self.event_ids)?;
+		Ok(Some(event_log))
+	}
+
 	#[rustfmt::skip]
 	pub fn set_config(&self, config: &mut WorldConfig) -> anyhow::Result<()> {
 		if let Some(cfg) = &self.cfg {
@@ -96,6 +153,33 @@ impl Args {
 			anyhow::bail!("need a speed and an FPS of at least 1 to export as GIF");
 		}
 
+		if self.gif.is_some() && self.metrics.is_some() {
+			anyhow::bail!("can't collect metrics while exporting a GIF");
+		}
+
+		if self.gif.is_some() && self.events.is_some() {
+			anyhow::bail!("can't log events while exporting a GIF");
+		}
+
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::Args;
+	use clap::Parser;
+
+	#[test]
+	fn no_recordings_on_resume() {
+		let parse =
+			|args: &[&str]| Args::try_parse_from(["antbyte", "world.json"].iter().chain(args));
+
+		assert!(parse(&["--resume"]).is_ok());
+		assert!(parse(&["--record", "run.json"]).is_ok());
+
+		let error = parse(&["--resume", "--record", "run.json"]).unwrap_err();
+		assert!(error.to_string().contains("cannot be used with"));
+		assert!(parse(&["--resume", "--replay", "run.json"]).is_err());
+	}
+}
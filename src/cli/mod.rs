@@ -11,7 +11,10 @@ use clap::{self, Parser};
 use crate::{
 	parser::compiler::LogConfig,
 	ui::term,
-	world::{World, config::WorldConfig, file_compiler::compile_world},
+	world::{
+		World, config::WorldConfig, file_compiler::compile_world, recording::Recording,
+		snapshot::Snapshot,
+	},
 };
 
 mod args;
@@ -25,6 +28,18 @@ pub fn create_world() -> Result<Option<(World, Args)>> {
 
 	let args = args;
 
+	if args.resume {
+		let mut snapshot = Snapshot::load(&args.path).context("snapshot error!")?;
+
+		args.set_config(&mut snapshot.properties.config)
+			.context("config-arg error!")?;
+
+		// checks the state against the config again, as it may now differ in size, layers or planes
+		let world = World::from_snapshot(snapshot).context("snapshot error!")?;
+
+		return Ok(Some((world, args)));
+	}
+
 	let log_config = LogConfig { all: args.debug };
 
 	let mut properties = compile_world(&args.path, &log_config, &args.sub_args)?;
@@ -45,22 +60,52 @@ pub fn create_world() -> Result<Option<(World, Args)>> {
 	} else {
 		args.set_config(&mut properties.config)
 			.context("config-arg error!")?;
-		let world = World::new(properties.clone()).context("world error!")?;
-
-		if let Some(target) = args.gif {
-			export_gif(world, &args.path, target).context("GIF export error!")?;
-		} else if args.raw {
-			term::raw::run(world);
+		let mut world = if let Some(path) = &args.replay {
+			let recording = Recording::load(path).context("replay error!")?;
+			World::replay(properties.clone(), recording)
 		} else {
-			return Ok(Some((world, args)));
+			World::new(properties.clone())
 		}
+		.context("world error!")?;
+
+		if args.record.is_some() {
+			world.record().context("recording error!")?;
+		}
+
+		return Ok(Some((world, args)));
 	}
 
 	Ok(None)
 }
 
+/// runs the world as set by the args until the simulation stops
+pub fn run_world(world: World, args: &Args) -> Result<World> {
+	let mut world = world;
+	let metrics = args.metrics_writer().context("metrics error!")?;
+
+	if let Some(event_log) = args.event_log().context("event log error!")? {
+		world.set_observer(Box::new(event_log));
+	}
+
+	let mut world = if let Some(target) = &args.gif {
+		export_gif(world, &args.path, target.clone()).context("GIF export error!")?
+	} else if args.raw {
+		term::raw::run(world, metrics).context("simulation error!")?
+	} else if args.headless {
+		term::headless::run(world, metrics).context("simulation error!")?
+	} else {
+		term::run(world, args.hide_title, metrics).context("simulation error!")?
+	};
+
+	if let Some(mut event_log) = world.take_observer() {
+		event_log.finish()?;
+	}
+
+	Ok(world)
+}
+
 #[rustfmt::skip]
-fn export_gif(world: World, source: &Path, target: Option<PathBuf>) -> Result<()> {
-	#[cfg(feature = "gif")] { crate::gif_export::gif_export(&world, source, target) }
+fn export_gif(world: World, source: &Path, target: Option<PathBuf>) -> Result<World> {
+	#[cfg(feature = "gif")] { crate::gif_export::gif_export(world, source, target) }
 	#[cfg(not(feature = "gif"))] { _ = (world, source, target); anyhow::bail!("need to enable the `gif` feature-flag in the antbyte crate"); }
 }
@@ -3,7 +3,11 @@
 use crate::{
 	ui::term::render::{clear_screen, print_title_short},
 	util::vec2::Pos,
-	world::{World, config::WorldConfig, frame::FrameOutput},
+	world::{
+		World,
+		config::{Topology, WorldConfig},
+		frame::FrameOutput,
+	},
 };
 
 use anyhow::Result;
@@ -16,7 +20,8 @@ use std::{
 const MAX_FRAMES: u32 = 0x400;
 const MAX_PX: u16 = 0x200;
 
-pub fn gif_export(world: &World, source: &Path, target: Option<PathBuf>) -> Result<()> {
+/// renders the world's next frames to a GIF, returning the world after the last one
+pub fn gif_export(world: World, source: &Path, target: Option<PathBuf>) -> Result<World> {
 	let path = match target {
 		Some(path) => path,
 		None => {
@@ -30,19 +35,16 @@ pub fn gif_export(world: &World, source: &Path, target: Option<PathBuf>) -> Resu
 
 	let WorldConfig { width, height, .. } = world.config();
 
-	let max_dim = (*width).max(*height);
+	let scale = tile_scale(world.config());
 
-	#[rustfmt::skip]
-	let scale = if max_dim <= MAX_PX { MAX_PX / max_dim } else { 1 }.max(1);
-
-	let scaled_width = width * scale;
+	let scaled_width = width * scale + hex_shift(world.config(), scale);
 	let scaled_height = height * scale;
 
 	let mut image = File::create(&path)?;
 	let mut encoder = Encoder::new(&mut image, scaled_width, scaled_height, &PALETTE)?;
 	encoder.set_repeat(Repeat::Infinite)?;
 
-	let mut world = world.clone();
+	let mut world = world;
 
 	for i in 0..MAX_FRAMES {
 		clear_screen();
@@ -57,7 +59,7 @@ pub fn gif_export(world: &World, source: &Path, target: Option<PathBuf>) -> Resu
 
 	println!("done!\nGIF exported as '{}'", path.to_string_lossy());
 
-	Ok(())
+	Ok(world)
 }
 
 fn render_frame(
@@ -73,7 +75,8 @@ fn render_frame(
 	let fps = fps.unwrap_or(30).clamp(1, 30);
 	let delay = (100.0 / fps as f32).round() as u16;
 
-	let scaled_width = width * scale;
+	let shift = hex_shift(config, scale);
+	let scaled_width = width * scale + shift;
 	let scaled_height = height * scale;
 
 	let mut scaled_pixels = Vec::with_capacity(scaled_width as usize * scaled_height as usize);
@@ -81,6 +84,10 @@ fn render_frame(
 	for y in 0..*height {
 		let mut scaled_row = Vec::with_capacity(scaled_width as usize);
 
+		// odd hex rows are shifted right by half a tile
+		let row_shift = if y % 2 == 1 { shift } else { 0 };
+		scaled_row.resize(row_shift as usize, 0);
+
 		for x in 0..*width {
 			let pos = Pos { x, y };
 			let pixel = *frame.bg.get(&pos).unwrap_or(&0);
@@ -91,6 +98,8 @@ fn render_frame(
 			}
 		}
 
+		scaled_row.resize(scaled_width as usize, 0);
+
 		for _ in 0..scale {
 			scaled_pixels.extend_from_slice(&scaled_row);
 		}
@@ -107,6 +116,28 @@ fn render_frame(
 	encoder.write_frame(&frame).unwrap();
 }
 
+/// size of a tile in pixels
+fn tile_scale(config: &WorldConfig) -> u16 {
+	let max_dim = config.width.max(config.height);
+
+	#[rustfmt::skip]
+	let scale = if max_dim <= MAX_PX { MAX_PX / max_dim } else { 1 }.max(1);
+
+	match config.topology {
+		Topology::Square => scale,
+		// even, so that odd rows shift by exactly half a tile, like in the terminal
+		Topology::Hex => (scale & !1).max(2),
+	}
+}
+
+/// extra width in pixels for shifting odd rows of hex worlds by half a tile
+fn hex_shift(config: &WorldConfig, scale: u16) -> u16 {
+	match config.topology {
+		Topology::Square => 0,
+		Topology::Hex => scale / 2,
+	}
+}
+
 const PALETTE: [u8; 0x10 * 3] = [
 	0x00, 0x00, 0x00, // 0: Black
 	0x80, 0x00, 0x00, // 1: Dark Red
@@ -125,3 +156,24 @@ const PALETTE: [u8; 0x10 * 3] = [
 	0x00, 0xFF, 0xFF, // 14: Bright Cyan
 	0xFF, 0xFF, 0xFF, // 15: White
 ];
+
+#[cfg(test)]
+mod test {
+	use super::{hex_shift, tile_scale};
+	use crate::world::config::{Topology, WorldConfig};
+
+	#[test]
+	fn hex_rows_shift_by_half_a_tile() {
+		for size in [3, 5, 100, 0x150, 0x200] {
+			let config = WorldConfig {
+				width: size,
+				height: size,
+				topology: Topology::Hex,
+				..Default::default()
+			};
+
+			let scale = tile_scale(&config);
+			assert_eq!(hex_shift(&config, scale) * 2, scale);
+		}
+	}
+}
@@ -11,11 +11,21 @@ fn main() {
 pub fn run() -> Result<()> {
 	#[cfg(feature = "term")]
 	{
-		use antbyte::ui::term;
+		use antbyte::cli;
+		use anyhow::Context;
+
+		if let Some((world, args)) = cli::create_world()? {
+			let world = cli::run_world(world, &args)?;
+
+			if let Some(path) = &args.save {
+				world.save_snapshot(path).context("snapshot error!")?;
+			}
 
-		if let Some((world, args)) = antbyte::cli::create_world()? {
-			use anyhow::Context;
-			term::run(world, args.hide_title).context("simulation error!")?;
+			if let Some(path) = &args.record
+				&& let Some(recording) = world.recording()
+			{
+				recording.save(path).context("recording error!")?;
+			}
 		}
 
 		Ok(())
@@ -42,7 +52,7 @@ pub fn run() -> Result<()> {
 		let properties = compile_world(&path, &LogConfig::default(), &None)?;
 		let world = World::new(properties.clone()).context("world error!")?;
 
-		term::run(world, true)?;
+		term::run(world, true, None)?;
 
 		Ok(())
 	}
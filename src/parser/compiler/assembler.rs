@@ -30,14 +30,7 @@ impl CompFunc {
 			eprintln!("{logic}");
 		}
 
-		let behavior = Behavior {
-			name: self.signature.name.clone(),
-			logic,
-			inputs,
-			outputs,
-		};
-
-		Ok(behavior)
+		Behavior::new(self.signature.name.clone(), logic, inputs, outputs)
 	}
 
 	fn extract_pins(&mut self) -> Result<(Vec<SubPin>, Vec<SubPin>)> {
@@ -17,14 +17,15 @@ use std::{
 use crate::{
 	ant::behavior::Behavior,
 	parser::{
-		AntFunc, ParamValue, Parser, Signature, SignatureSpec,
+		AntFunc, AntSpawn, ParamValue, Parser, Signature, SignatureSpec,
 		compiler::{func_comp::compile_funcs, stdlib::STDLIB},
+		token::Token,
 	},
 	truth_table::TruthTable,
-	world::WorldProperties,
+	world::{WorldProperties, config::StartAnt},
 };
 
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
 
 #[derive(Debug, Clone)]
 struct CompFunc {
@@ -145,6 +146,35 @@ pub fn compile_world(
 		}
 	}
 
+	for AntSpawn { target, fields } in parsed_world.spawns {
+		let behavior = match target {
+			Token::Ident(name) => behaviors
+				.iter()
+				.find(|(_, behavior)| behavior.name == name)
+				.map(|(&id, _)| id)
+				.ok_or(anyhow!("tried to spawn undefined ant '{name}'"))?,
+			Token::Number(id) => {
+				u8::try_from(id).map_err(|_| anyhow!("tried to spawn ant with invalid ID #{id}"))?
+			}
+			Token::Bit(id) => id as u8,
+			_ => unreachable!(),
+		};
+
+		let mut start_ant = StartAnt {
+			behavior,
+			..Default::default()
+		};
+
+		for (key, value) in fields {
+			start_ant.set_field(key, value)?;
+		}
+
+		properties.config.start_ants.push(start_ant);
+	}
+
+	// once all settings are applied, as they may depend on each other.
+	// start ants are only checked by the world, after any overrides
+	properties.config.validate()?;
 	properties.behaviors = behaviors;
 
 	Ok(properties)
@@ -162,3 +192,33 @@ pub fn compile_func(code: &str, signature: SignatureSpec) -> TruthTable {
 
 	func.assemble(&log_cfg).unwrap().logic
 }
+
+#[cfg(test)]
+mod test {
+	use super::compile_world_simple;
+	use crate::world::World;
+
+	#[test]
+	fn start_ants() {
+		// only checked by the world, after any overrides
+		let code = "set size = 4; ant a = 1 { H = 1; } spawn a { x = 7; }";
+		let mut properties = compile_world_simple(code).unwrap();
+		assert!(World::new(properties.clone()).is_err());
+
+		properties.config.width = 8;
+		assert!(World::new(properties).is_ok());
+
+		assert!(compile_world_simple("ant a = 1 { H = 1; } spawn 257;").is_err());
+	}
+
+	#[test]
+	fn validate_settings() {
+		let ant = "ant a = 1 { H = 1; }";
+		let compile = |settings: &str| compile_world_simple(&format!("{settings} {ant}"));
+
+		// in any order
+		assert!(compile("set plane_1 = 1; set planes = 2; set layers = 2;").is_ok());
+		assert!(compile("set planes = 2; set layers = 2; set plane_1 = 2;").is_err());
+		assert!(compile("set size = 2;").is_err());
+	}
+}
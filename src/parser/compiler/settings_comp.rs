@@ -3,7 +3,12 @@ use anyhow::{Context, Result, anyhow};
 use crate::{
 	parser::{Parser, token::Token},
 	util::vec2::Coord,
-	world::config::{BorderMode, ByteFilter, RenderMask, StartingPos, WorldConfig},
+	world::config::{
+		BorderMode, ByteFilter, ConflictStrategy, LayerOrder, MAX_ENERGY, Neighborhood, RandomMode,
+		RenderMask, StartAnt, StartingPos, Topology, WorldConfig,
+	},
+	world::cycle::CycleMode,
+	world::watch::{Watch, WatchAction, WatchCondition},
 };
 
 impl WorldConfig {
@@ -18,16 +23,45 @@ impl WorldConfig {
 	}
 }
 
+impl StartAnt {
+	pub fn set_field(&mut self, key: String, value: Token) -> Result<()> {
+		let mut parser = Parser {
+			tokens: vec![value],
+		};
+
+		let value = parser
+			.next_number()
+			.with_context(|| format!("for start ant field '{key}'!"))?
+			.unwrap_or_default();
+
+		match key.as_str() {
+			"x" => self.pos.get_or_insert_default().x = value as Coord,
+			"y" => self.pos.get_or_insert_default().y = value as Coord,
+			"layer" => self.layer = value as u8,
+			"dir" => self.dir = value as u8,
+			"mem" | "memory" => self.memory = value as u8,
+			other => return Err(anyhow!("unknown start ant field: '{other}'")),
+		}
+
+		Ok(())
+	}
+}
+
 impl Parser {
-	fn set_setting(&mut self, config: &mut WorldConfig, key: &str) -> Result<()> {
-		let key_parts = key.rsplit_once('_');
+	fn set_setting(&mut self, config: &mut WorldConfig, setting: &str) -> Result<()> {
+		let key_parts = setting.rsplit_once('_');
 
 		let (key, sub_index) = match key_parts {
-			Some((key, suffix)) => match u8::from_str_radix(suffix, 8) {
-				Ok(index) => (key.to_owned(), index),
-				Err(_) => (format!("{key}_{suffix}"), 0),
-			},
-			None => (key.to_owned(), 0),
+			Some((key, suffix)) => {
+				// behavior IDs are decimal, like everywhere else
+				let radix = if key == "energy" { 10 } else { 8 };
+
+				match u8::from_str_radix(suffix, radix) {
+					Ok(index) => (key.to_owned(), index),
+					Err(_) => (format!("{key}_{suffix}"), 0),
+				}
+			}
+			None => (setting.to_owned(), 0),
 		};
 
 		match key.as_str() {
@@ -49,10 +83,32 @@ impl Parser {
 
 			"layers" => config.layers = self.next_number()?.unwrap_or_default() as u8,
 			"main_layer" => config.main_layer = self.next_number()?.unwrap_or_default() as u8,
+			"layer_order" => config.layer_order = LayerOrder::try_from(self.next_ident()?)?,
+			"layer_period" => {
+				_ = config
+					.layer_period
+					.insert(sub_index, self.next_number()?.unwrap_or_default())
+			}
+			"layer_steps" => {
+				_ = config
+					.layer_steps
+					.insert(sub_index, self.next_number()?.unwrap_or_default())
+			}
+
+			"planes" => config.planes = self.next_number()?.unwrap_or_default() as u8,
+			"plane" => {
+				_ = config
+					.plane
+					.insert(sub_index, self.next_number()?.unwrap_or_default() as u8)
+			}
+
+			"tiles" => _ = config.tiles.insert(sub_index, self.next_str()?),
 
 			"fps" => config.fps = self.next_number()?,
 			"speed" => config.speed = self.next_number()?,
-			"decay" => config.decay = self.next_number().map(|x| x.map(|v| v as u16))?,
+			"decay" => config.decay = self.next_number()?,
+			"diffusion" => config.diffusion = self.next_number()?.unwrap_or_default(),
+			"evaporation" | "evap" => config.evaporation = self.next_number()?.unwrap_or_default(),
 			"sleep" => config.sleep = self.next_number()?,
 			"ticks" => config.max_ticks = self.next_number()?,
 			"seed" => config.seed = self.next_number()?,
@@ -78,12 +134,43 @@ impl Parser {
 				config.border.insert(sub_index, border_mode);
 			}
 
+			"topology" => config.topology = Topology::try_from(self.next_ident()?)?,
+			"neighborhood" => config.neighborhood = Neighborhood::try_from(self.next_ident()?)?,
+
+			"cycles" => config.cycles = CycleMode::try_from(self.next_ident()?)?,
+
+			key @ ("pause_on" | "end_on") => {
+				let condition = WatchCondition::try_from(self.next_str()?)?;
+
+				let action = match key {
+					"pause_on" => WatchAction::Pause,
+					"end_on" => WatchAction::End,
+					_ => unreachable!(),
+				};
+
+				config.watch.push(Watch { condition, action });
+			}
+
 			"start_pos" | "start" => config.start_pos = StartingPos::try_from(self.next_ident()?)?,
 			"start_dir" => config.start_dir = self.next_number()?.unwrap_or_default() as u8,
 			"start_tick" => config.start_tick = self.next_number()?.unwrap_or_default(),
+			"conflict" => config.conflict = ConflictStrategy::try_from(self.next_ident()?)?,
+			"random" => config.random = RandomMode::try_from(self.next_ident()?)?,
+			"energy" => {
+				let start = self.next_number()?.unwrap_or_default();
+				WorldConfig::cap(start, setting, MAX_ENERGY)?;
+				config.energy.start.insert(sub_index, start);
+			}
+			"energy_tick" => config.energy.tick_cost = self.next_number()?.unwrap_or_default(),
+			"energy_move" => config.energy.move_cost = self.next_number()?.unwrap_or_default(),
+			"energy_spawn" => config.energy.spawn_cost = self.next_number()?.unwrap_or_default(),
+			"energy_write" => config.energy.write_cost = self.next_number()?.unwrap_or_default(),
+			"energy_gain" => config.energy.gain = self.next_number()?.unwrap_or_default(),
+
 			"ant_limit" => config.ant_limit = self.next_number()?.unwrap_or_default(),
 
 			"bg_filter" => config.bg_filter = ByteFilter::try_from(self.next_ident()?)?,
+			"bg_plane" => config.bg_plane = self.next_number()?.unwrap_or_default() as u8,
 			"bg" => config.bg = RenderMask::try_from(self.next_ident()?)?,
 			"fg" => config.fg = RenderMask::try_from(self.next_ident()?)?,
 
@@ -108,8 +195,7 @@ impl Parser {
 			other => return Err(anyhow!("unknown setting: '{other}'")),
 		}
 
-		// double-check config validity
-		config.validate()?;
+		// validated by `compile_world` once all settings are applied, as they may depend on each other
 
 		Ok(())
 	}
@@ -12,7 +12,7 @@ use std::fmt::Display;
 
 #[rustfmt::skip]
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Keyword { Set, Fn, Ant, Use, UseCfg, NoStd }
+pub enum Keyword { Set, Fn, Ant, Spawn, Use, UseCfg, NoStd }
 
 impl Keyword {
 	pub(super) fn from_ident(ident: &str) -> Option<Self> {
@@ -20,6 +20,7 @@ impl Keyword {
 			"set" => Some(Self::Set),
 			"fn" => Some(Self::Fn),
 			"ant" => Some(Self::Ant),
+			"spawn" => Some(Self::Spawn),
 			"use" => Some(Self::Use),
 			"use_cfg" => Some(Self::UseCfg),
 			"no_std" => Some(Self::NoStd),
@@ -33,6 +34,7 @@ struct ParsedWorld {
 	settings: Vec<(String, Token)>,
 	funcs: Vec<Func>,
 	ants: Vec<AntFunc>,
+	spawns: Vec<AntSpawn>,
 	imports: Vec<WorldImport>,
 	no_std: bool,
 }
@@ -145,6 +147,13 @@ struct AntFunc {
 	target_id: u8,
 }
 
+/// a start ant, with its behavior given by name or ID
+#[derive(Debug)]
+struct AntSpawn {
+	target: Token,
+	fields: Vec<(String, Token)>,
+}
+
 #[derive(Default)]
 pub struct Parser {
 	tokens: Vec<Token>,
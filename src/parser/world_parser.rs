@@ -1,6 +1,6 @@
 use crate::parser::compiler::linker::{WorldImport, WorldImportMode};
 
-use super::{Keyword, ParsedWorld, Parser, Token};
+use super::{AntSpawn, Keyword, ParsedWorld, Parser, Token};
 use anyhow::{Context, Result};
 
 impl Parser {
@@ -75,6 +75,24 @@ impl Parser {
 					world.funcs.push(func);
 					world.ants.push(ant);
 				}
+				Spawn => {
+					let target = match self.next_token() {
+						target @ (Token::Ident(_) | Token::Number(_) | Token::Bit(_)) => target,
+						other => return Err(Parser::unexpected(other, "ant name or ID")),
+					};
+
+					let mut fields = vec![];
+
+					if self.assume_next(Token::Semicolon).is_none() {
+						self.expect_next(Token::BraceLeft)?;
+
+						while self.assume_next(Token::BraceRight).is_none() {
+							fields.push(self.parse_setting()?);
+						}
+					}
+
+					world.spawns.push(AntSpawn { target, fields });
+				}
 				NoStd => {
 					world.no_std = true;
 					self.expect_next(Token::Semicolon)?;
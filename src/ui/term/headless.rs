@@ -0,0 +1,42 @@
+use anyhow::Result;
+
+use crate::world::{World, frame::FrameInput, metrics::MetricsWriter};
+
+/// runs the world without rendering or waiting until it ends or is interrupted,
+/// returning it in its final state
+pub fn run(world: World, metrics: Option<MetricsWriter>) -> Result<World> {
+	let mut world = world;
+	let mut metrics = metrics;
+
+	if metrics.is_some() {
+		world.collect_metrics();
+	}
+
+	let ctrl_c_rx = crate::util::setup_ctrl_c();
+
+	// there are no keys to read, so the external input stays 0
+	while world.next_frame(&FrameInput::default()).is_some() {
+		if let Some(metrics) = &mut metrics {
+			metrics.write(&world.take_samples())?;
+		}
+
+		// pausing watches have nothing to pause, so the run goes on
+		if let Some(watch_event) = world.watch_event() {
+			eprintln!("<!> {watch_event}");
+		}
+
+		if ctrl_c_rx.as_ref().is_some_and(|rx| rx.try_recv().is_ok()) {
+			break;
+		}
+	}
+
+	if let Some(metrics) = &mut metrics {
+		metrics.flush()?;
+	}
+
+	if let Some(cycle) = world.cycle() {
+		eprintln!("<i> {cycle}");
+	}
+
+	Ok(world)
+}
@@ -6,16 +6,26 @@ use anyhow::Result;
 use crate::{
 	ui::term::render::TermRenderer,
 	util::sleep,
-	world::{World, frame::FrameInput},
+	world::{World, frame::FrameInput, metrics::MetricsWriter, watch::WatchAction},
 };
 use std::{io, time::Instant};
 
+pub mod headless;
 pub mod keyboard;
 pub mod raw;
 pub mod render;
 
-pub fn run(world: World, hide_title: bool) -> Result<()> {
+/// number of past frames that can be stepped back to in stepped mode
+const HISTORY_CAP: usize = 0x100;
+
+/// runs the world until it ends or is interrupted, returning it in its final state
+pub fn run(world: World, hide_title: bool, metrics: Option<MetricsWriter>) -> Result<World> {
 	let mut world = world;
+	let mut metrics = metrics;
+
+	if metrics.is_some() {
+		world.collect_metrics();
+	}
 
 	let renderer = TermRenderer {
 		hide_title,
@@ -31,19 +41,44 @@ pub fn run(world: World, hide_title: bool) -> Result<()> {
 
 	let mut last_frame = Instant::now();
 
-	while let Some(frame) = world.next_frame(&FrameInput {
-		ext_in: keyboard::get_keys(world.config()),
-	}) {
+	// ticks written to the metrics can't be taken back
+	let history_cap = if metrics.is_some() { 0 } else { HISTORY_CAP };
+
+	// stays paused once a watch pauses the simulation, without changing the world's config
+	let mut stepped = world.config().fps.is_none();
+
+	if stepped {
+		world.set_history(history_cap);
+	}
+
+	let mut next_frame = world.next_frame(&frame_input(&world));
+
+	while let Some(frame) = next_frame {
 		if ctrl_c_rx.as_ref().is_some_and(|rx| rx.try_recv().is_ok()) {
 			break;
 		}
 
 		renderer.render_frame(&frame);
 
+		if let Some(metrics) = &mut metrics {
+			metrics.write(&world.take_samples())?;
+		}
+
+		let paused = (frame.watch_event.as_ref())
+			.is_some_and(|event| event.watch.action == WatchAction::Pause);
+
+		if paused && !stepped {
+			// paused by a watch => allow stepping back from here on
+			stepped = true;
+			world.set_history(history_cap);
+		}
+
 		#[cfg(feature = "midi")]
 		player.transmit(&frame.ext_out);
 
-		if let Some(frame_ms) = frame.ms {
+		if let Some(frame_ms) = frame.ms
+			&& !stepped
+		{
 			// wait for frame interval to elapse
 			let elapsed = last_frame.elapsed().as_millis() as u32;
 			if elapsed < frame_ms {
@@ -54,11 +89,43 @@ pub fn run(world: World, hide_title: bool) -> Result<()> {
 			last_frame = Instant::now();
 		} else {
 			// wait for key input to continue
-			eprintln!("<i> Press <Enter> to step to next frame");
+			if metrics.is_some() {
+				eprintln!("<i> Press <Enter> to step to next frame");
+			} else {
+				eprintln!("<i> Press <Enter> to step to next frame, or <B> <Enter> to step back");
+			}
+
 			let mut input = String::new();
 			io::stdin().read_line(&mut input).unwrap();
+
+			if input.trim().eq_ignore_ascii_case("b") {
+				if metrics.is_some() {
+					eprintln!("<i> Can't step back while collecting metrics");
+				} else if !world.step_back() {
+					eprintln!("<i> Reached the start of the history");
+				}
+
+				next_frame = Some(world.current_frame());
+				continue;
+			}
 		}
+
+		next_frame = world.next_frame(&frame_input(&world));
 	}
 
-	Ok(())
+	if let Some(metrics) = &mut metrics {
+		metrics.flush()?;
+	}
+
+	Ok(world)
+}
+
+fn frame_input(world: &World) -> FrameInput {
+	FrameInput {
+		ext_in: if world.replaying() {
+			0
+		} else {
+			keyboard::get_keys(world.config())
+		},
+	}
 }
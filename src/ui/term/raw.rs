@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
 
+use anyhow::Result;
+
 use crate::{
 	ui::chars_to_input,
 	util::vec2::Pos,
@@ -7,11 +9,17 @@ use crate::{
 		World,
 		config::{RenderMask, WorldConfig},
 		frame::FrameInput,
+		metrics::MetricsWriter,
 	},
 };
 
-pub fn run(world: World) {
+pub fn run(world: World, metrics: Option<MetricsWriter>) -> Result<World> {
 	let mut world = world;
+	let mut metrics = metrics;
+
+	if metrics.is_some() {
+		world.collect_metrics();
+	}
 
 	print!("\n\n");
 
@@ -19,6 +27,10 @@ pub fn run(world: World) {
 	let mut input = FrameInput::default();
 
 	while let Some(frame) = world.next_frame(&input) {
+		if let Some(metrics) = &mut metrics {
+			metrics.write(&world.take_samples())?;
+		}
+
 		// ## FG
 		if let RenderMask::None = world.config().fg {
 			println!("--");
@@ -45,11 +57,27 @@ pub fn run(world: World) {
 		println!("{metadata}");
 		println!();
 
+		if let Some(watch_event) = &frame.watch_event {
+			eprintln!("<!> {watch_event}");
+		}
+
 		// ## External Input
-		input_str.clear();
-		std::io::stdin().read_line(&mut input_str).unwrap();
-		input.ext_in = chars_to_input(&world.config().keys, &input_str);
+		if !world.replaying() {
+			input_str.clear();
+			std::io::stdin().read_line(&mut input_str).unwrap();
+			input.ext_in = chars_to_input(&world.config().keys, &input_str);
+		}
+	}
+
+	if let Some(metrics) = &mut metrics {
+		metrics.flush()?;
 	}
+
+	if let Some(cycle) = world.cycle() {
+		eprintln!("<i> {cycle}");
+	}
+
+	Ok(world)
 }
 
 fn print_grid(config: &WorldConfig, grid: &BTreeMap<Pos, u8>) {
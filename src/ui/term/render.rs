@@ -1,7 +1,7 @@
 use crate::{
 	util::{dir::Direction, vec2::Pos},
 	world::{
-		config::{RenderMask, WorldConfig},
+		config::{RenderMask, Topology, WorldConfig},
 		frame::FrameOutput,
 	},
 };
@@ -52,6 +52,14 @@ impl TermRenderer {
 		println!("\n\n{world_str}\n\n");
 		println!("{}", frame.metadata);
 
+		if let Some(watch_event) = &frame.watch_event {
+			println!("\n<!> {watch_event}");
+		}
+
+		if let Some(cycle) = &frame.cycle {
+			println!("\n<i> {cycle}");
+		}
+
 		io::stdout().flush().unwrap();
 	}
 
@@ -64,6 +72,11 @@ impl TermRenderer {
 
 			if pos.x == 0 {
 				string.push('\n');
+
+				// shift odd hex rows by half a tile
+				if self.config.topology == Topology::Hex && pos.y % 2 == 1 {
+					string.push(' ');
+				}
 			}
 
 			let tile_color = frame.bg.get(&pos).unwrap_or(&0);
@@ -72,10 +85,7 @@ impl TermRenderer {
 			let tile_text = match fg_value {
 				None => "  ",
 				Some(&fg_value) => match self.config.fg {
-					RenderMask::Dir => {
-						let dir = Direction::from(fg_value);
-						&dir.as_string()
-					}
+					RenderMask::Dir => &self.config.lattice().dir_string(Direction::from(fg_value)),
 					RenderMask::None => "  ",
 					_ => &format!("{fg_value:02X}"),
 				},
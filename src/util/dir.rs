@@ -1,8 +1,10 @@
 use std::ops;
 
+use serde::{Deserialize, Serialize};
+
 use crate::util::vec2::Vec2;
 
-#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Direction(u8);
 
 impl Direction {
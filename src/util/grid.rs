@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::util::vec2::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Grid<T> {
 	pub width: Coord,
 	pub height: Coord,
@@ -19,7 +21,7 @@ impl<T> Default for Grid<T> {
 
 impl<T> Grid<T> {
 	pub fn with_entries(width: Coord, height: Coord, entries: Vec<T>) -> Self {
-		assert_eq!(entries.len(), (width * height) as usize);
+		assert_eq!(entries.len(), width as usize * height as usize);
 
 		Self {
 			width,
@@ -33,11 +35,16 @@ impl<T> Grid<T> {
 		x >= 0 && y >= 0 && y < self.height as i32 && x < self.width as i32
 	}
 
+	/// index computed as usize, since `y * width` may overflow a `Coord`
+	#[inline]
+	fn index(&self, pos: Pos) -> usize {
+		pos.y as usize * self.width as usize + pos.x as usize
+	}
+
 	#[inline]
 	pub fn get(&self, pos: Pos) -> Option<&T> {
-		let Pos { x, y } = pos;
 		if self.in_bounds(&pos.sign()) {
-			Some(&self.entries[(y * self.width + x) as usize])
+			Some(&self.entries[self.index(pos)])
 		} else {
 			None
 		}
@@ -46,7 +53,8 @@ impl<T> Grid<T> {
 	#[inline]
 	pub fn set(&mut self, pos: Pos, value: T) {
 		if self.in_bounds(&pos.sign()) {
-			self.entries[(pos.y * self.width + pos.x) as usize] = value;
+			let index = self.index(pos);
+			self.entries[index] = value;
 		} else {
 			panic!("map index is out of range: {pos:?}")
 		}
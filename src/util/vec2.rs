@@ -1,10 +1,13 @@
 use std::ops;
 
+use serde::{Deserialize, Serialize};
+
 use crate::util::hash_u32;
 
 pub type Coord = u16;
 
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Pos {
 	pub x: Coord,
 	pub y: Coord,
@@ -0,0 +1,310 @@
+use crate::{
+	ant::Ant,
+	util::{grid::Grid, vec2::Pos},
+};
+
+const VACANT: u32 = u32::MAX;
+
+/// the ants of a single layer
+///
+/// a dense occupancy grid points into a compact arena that is kept sorted by position,
+/// so lookups are O(1) while iteration order stays the same as for a `BTreeMap<Pos, Ant>`
+///
+/// removed ants leave a tombstone in the arena until the next compaction.
+/// an arena slot is alive if and only if its cell points back to it
+#[derive(Debug, Clone, Default)]
+pub struct Ants {
+	cells: Grid<u32>,
+	arena: Vec<(Pos, Ant)>,
+	len: usize,
+	/// whether ants were appended out of order since the last sort
+	unsorted: bool,
+}
+
+impl Ants {
+	pub fn new(width: u16, height: u16) -> Self {
+		Self {
+			cells: Grid::with_entries(
+				width,
+				height,
+				vec![VACANT; width as usize * height as usize],
+			),
+			arena: vec![],
+			len: 0,
+			unsorted: false,
+		}
+	}
+
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	#[inline]
+	fn slot(&self, pos: &Pos) -> Option<usize> {
+		match self.cells.get(*pos) {
+			Some(&VACANT) | None => None,
+			Some(&index) => Some(index as usize),
+		}
+	}
+
+	#[inline]
+	fn alive(&self, index: usize) -> bool {
+		self.slot(&self.arena[index].0) == Some(index)
+	}
+
+	#[inline]
+	pub fn contains_key(&self, pos: &Pos) -> bool {
+		self.slot(pos).is_some()
+	}
+
+	#[inline]
+	pub fn get(&self, pos: &Pos) -> Option<&Ant> {
+		self.slot(pos).map(|index| &self.arena[index].1)
+	}
+
+	#[inline]
+	pub fn get_mut(&mut self, pos: &Pos) -> Option<&mut Ant> {
+		self.slot(pos).map(|index| &mut self.arena[index].1)
+	}
+
+	/// inserts an ant, returning the ant previously at that position
+	pub fn insert(&mut self, pos: Pos, ant: Ant) -> Option<Ant> {
+		debug_assert!(!self.unsorted, "inserting into unsorted ants");
+
+		if let Some(index) = self.slot(&pos) {
+			return Some(std::mem::replace(&mut self.arena[index].1, ant));
+		}
+
+		let index = match self.arena.binary_search_by_key(&pos, |(pos, _)| *pos) {
+			// reuse the tombstone at this position
+			Ok(index) => {
+				self.arena[index].1 = ant;
+				index
+			}
+			Err(index) => {
+				self.arena.insert(index, (pos, ant));
+
+				// shift the indices of all live slots after the inserted one
+				for shifted in index + 1..self.arena.len() {
+					let shifted_pos = self.arena[shifted].0;
+
+					if self.slot(&shifted_pos) == Some(shifted - 1) {
+						self.cells.set(shifted_pos, shifted as u32);
+					}
+				}
+
+				index
+			}
+		};
+
+		self.cells.set(pos, index as u32);
+		self.len += 1;
+
+		None
+	}
+
+	/// inserts many ants at once, only sorting the arena once at the end
+	pub fn extend(&mut self, ants: impl IntoIterator<Item = (Pos, Ant)>) {
+		for (pos, ant) in ants {
+			self.append(pos, ant);
+		}
+
+		self.sort();
+	}
+
+	/// inserts an ant in O(1) without keeping the arena sorted, returning the ant previously at that position.
+	///
+	/// `sort` needs to be called before iterating again
+	pub fn append(&mut self, pos: Pos, ant: Ant) -> Option<Ant> {
+		if let Some(index) = self.slot(&pos) {
+			return Some(std::mem::replace(&mut self.arena[index].1, ant));
+		}
+
+		if self.arena.last().is_some_and(|(last, _)| *last >= pos) {
+			self.unsorted = true;
+		}
+
+		self.cells.set(pos, self.arena.len() as u32);
+		self.arena.push((pos, ant));
+		self.len += 1;
+
+		None
+	}
+
+	/// restores the order of the arena after appending
+	pub fn sort(&mut self) {
+		if !self.unsorted {
+			return;
+		}
+
+		self.compact();
+		self.arena.sort_unstable_by_key(|(pos, _)| *pos);
+
+		for (index, (pos, _)) in self.arena.iter().enumerate() {
+			self.cells.set(*pos, index as u32);
+		}
+
+		self.unsorted = false;
+	}
+
+	/// removes an ant in O(1) by leaving a tombstone
+	pub fn remove(&mut self, pos: &Pos) -> Option<Ant> {
+		let index = self.slot(pos)?;
+		self.cells.set(*pos, VACANT);
+		self.len -= 1;
+		Some(self.arena[index].1)
+	}
+
+	pub fn retain(&mut self, mut keep: impl FnMut(&Pos, &mut Ant) -> bool) {
+		let mut kept = 0;
+
+		for index in 0..self.arena.len() {
+			if !self.alive(index) {
+				continue;
+			}
+
+			let (pos, ant) = &mut self.arena[index];
+			let pos = *pos;
+
+			if keep(&pos, ant) {
+				self.arena.swap(kept, index);
+				self.cells.set(pos, kept as u32);
+				kept += 1;
+			} else {
+				self.cells.set(pos, VACANT);
+			}
+		}
+
+		self.arena.truncate(kept);
+		self.len = kept;
+	}
+
+	/// drops all tombstones
+	fn compact(&mut self) {
+		self.retain(|_, _| true);
+	}
+
+	/// removes all ants, but keeps the allocations
+	pub fn clear(&mut self) {
+		for (pos, _) in self.arena.drain(..) {
+			self.cells.set(pos, VACANT);
+		}
+
+		self.len = 0;
+		self.unsorted = false;
+	}
+
+	/// iterates over all ants, ordered by position
+	pub fn iter(&self) -> impl Iterator<Item = (Pos, &Ant)> {
+		debug_assert!(!self.unsorted, "iterating over unsorted ants");
+
+		self.arena
+			.iter()
+			.enumerate()
+			.filter(|&(index, _)| self.alive(index))
+			.map(|(_, (pos, ant))| (*pos, ant))
+	}
+
+	pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Ant> {
+		self.iter_mut().map(|(_, ant)| ant)
+	}
+
+	/// iterates mutably over all ants, ordered by position
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (Pos, &mut Ant)> {
+		debug_assert!(!self.unsorted, "iterating over unsorted ants");
+
+		let cells = &self.cells;
+
+		self.arena
+			.iter_mut()
+			.enumerate()
+			.filter(|(index, (pos, _))| cells.get(*pos) == Some(&(*index as u32)))
+			.map(|(_, (pos, ant))| (*pos, ant))
+	}
+
+	/// positions of all ants, ordered
+	#[inline]
+	pub fn positions(&self) -> impl Iterator<Item = Pos> {
+		self.iter().map(|(pos, _)| pos)
+	}
+
+	/// first ant (by position) in the arena, starting the search at `from`.
+	/// returns the arena index to continue the search from
+	pub(super) fn next_from(&self, from: usize) -> Option<(usize, Pos, Ant)> {
+		(from..self.arena.len())
+			.find(|&index| self.alive(index))
+			.map(|index| (index + 1, self.arena[index].0, self.arena[index].1))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::BTreeMap;
+
+	use super::Ants;
+	use crate::{
+		ant::Ant,
+		util::{hash_u32, vec2::Pos},
+	};
+
+	#[test]
+	fn same_order_as_btree_map() {
+		let mut ants = Ants::new(12, 9);
+		let mut expected = BTreeMap::<Pos, Ant>::new();
+		let mut appended = vec![];
+
+		for step in 0..2000 {
+			let hash = hash_u32(step);
+			let pos = Pos {
+				x: (hash % 12) as u16,
+				y: (hash >> 8) as u16 % 9,
+			};
+			let ant = Ant {
+				behavior: (hash >> 16) as u8,
+				..Default::default()
+			};
+
+			match hash >> 28 {
+				0..6 => {
+					let prev = ants.insert(pos, ant).map(|ant| ant.behavior);
+					assert_eq!(prev, expected.insert(pos, ant).map(|ant| ant.behavior));
+				}
+				6..10 => {
+					let prev = ants.remove(&pos).map(|ant| ant.behavior);
+					assert_eq!(prev, expected.remove(&pos).map(|ant| ant.behavior));
+				}
+				10..12 => {
+					let odd = |ant: &Ant| ant.behavior % 2 == 1;
+					ants.retain(|_, ant| odd(ant));
+					expected.retain(|_, ant| odd(ant));
+				}
+				_ => {
+					// batched like in move ticks, sorted once the batch is done
+					appended.push((pos, ant));
+
+					if appended.len() == 8 {
+						for (pos, ant) in appended.drain(..) {
+							let prev = ants.append(pos, ant).map(|ant| ant.behavior);
+							assert_eq!(prev, expected.insert(pos, ant).map(|ant| ant.behavior));
+						}
+
+						ants.sort();
+					}
+				}
+			}
+
+			if appended.is_empty() {
+				let actual = ants.iter().map(|(pos, ant)| (pos, ant.behavior));
+				let expected = expected.iter().map(|(pos, ant)| (*pos, ant.behavior));
+				assert!(actual.eq(expected), "order differs after step {step}");
+				assert_eq!(ants.len(), ants.iter().count());
+			}
+		}
+	}
+}
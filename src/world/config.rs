@@ -1,18 +1,27 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::{Error, Result, anyhow, bail};
 use serde::{Deserialize, Serialize};
 
-use crate::util::{
-	dir::Direction,
-	vec2::{Coord, Pos},
+use crate::{
+	util::{
+		dir::Direction,
+		vec2::{Coord, Pos, Vec2},
+	},
+	world::{
+		cycle::CycleMode,
+		watch::{Watch, WatchCondition},
+	},
 };
 
 pub const FPS_CAP: u32 = 50;
 pub const SPEED_CAP: u32 = 0x4000;
 pub const SIZE_CAP: Coord = 0x200;
 pub const LAYER_CAP: u8 = 8;
+pub const PLANE_CAP: u8 = 8;
+pub const LAYER_STEPS_CAP: u32 = 0x10;
 const ANT_LIMIT: u32 = 0x4000;
+pub const MAX_ENERGY: u32 = u16::MAX as u32;
 
 #[cfg_attr(test, derive(ts_rs::TS))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -28,24 +37,57 @@ pub struct WorldConfig {
 	pub layers: u8,
 	/// layer that will be rendered
 	pub main_layer: u8,
+	/// number of tile planes
+	pub planes: u8,
+	/// tile plane used by each layer (defaults to plane 0)
+	pub plane: BTreeMap<u8, u8>,
+	/// path of a text map or PGM image with the initial tiles of each plane.
+	/// map tiles are not subject to decay
+	pub tiles: BTreeMap<u8, String>,
 	/// simulated ticks per frame (defaults to 1)
 	pub speed: Option<u32>,
 	/// simulation tick limit
 	pub max_ticks: Option<u32>,
+	/// order in which the layers are updated each tick
+	pub layer_order: LayerOrder,
+	/// ticks between the updates of each layer (defaults to 1)
+	pub layer_period: BTreeMap<u8, u32>,
+	/// updates of each layer per updating tick (defaults to 1)
+	pub layer_steps: BTreeMap<u8, u32>,
 	/// amount of ticks after which a tile will automatically reset
-	pub decay: Option<u16>,
+	pub decay: Option<u32>,
+	/// share of each tile's field value (out of 256) spread evenly to its neighbors each tick
+	pub diffusion: u32,
+	/// share of each tile's field value (out of 256) lost each tick
+	pub evaporation: u32,
 	/// re-run simulation after it ends
 	pub looping: bool,
 	/// behavior if ants touch the worlds border
 	pub border: BTreeMap<u8, BorderMode>,
+	/// shape of the tiles and their neighborhood
+	pub topology: Topology,
+	/// whether square tiles also neighbor diagonally
+	pub neighborhood: Neighborhood,
 	/// position of the first ant
 	pub start_pos: StartingPos,
 	/// first tick to render
 	pub start_tick: u32,
-	/// direction value (0-7) for start ant
+	/// direction value for start ant (0-7, 0-5 in hex worlds, 0-3 with von neumann neighborhood)
 	pub start_dir: u8,
+	/// initial ants, replacing the single start ant if not empty
+	pub start_ants: Vec<StartAnt>,
 	/// max number of ants before additional spawning gets blocked
 	pub ant_limit: u32,
+	/// how move and spawn conflicts between ants are decided
+	pub conflict: ConflictStrategy,
+	/// source of random inputs and random conflict priorities
+	pub random: RandomMode,
+	/// conditions that pause or end the simulation
+	pub watch: Vec<Watch>,
+	/// whether to detect repeating states
+	pub cycles: CycleMode,
+	/// costs and gains of the optional energy model
+	pub energy: EnergyConfig,
 	pub seed: Option<u32>,
 	pub description: String,
 
@@ -56,6 +98,8 @@ pub struct WorldConfig {
 	pub bg_filter: ByteFilter,
 	/// background render mask
 	pub bg: RenderMask,
+	/// tile plane that will be rendered as background
+	pub bg_plane: u8,
 	/// foreground render mask
 	pub fg: RenderMask,
 	/// amount of ms to sleep for after end of simulation, i.e. between loops
@@ -75,14 +119,30 @@ impl Default for WorldConfig {
 			height: 16,
 			layers: 1,
 			main_layer: 0,
+			planes: 1,
+			plane: BTreeMap::new(),
+			tiles: BTreeMap::new(),
 			speed: Some(1),
 			max_ticks: None,
+			layer_order: LayerOrder::Descending,
+			layer_period: BTreeMap::new(),
+			layer_steps: BTreeMap::new(),
 			decay: None,
+			diffusion: 0,
+			evaporation: 0,
 			looping: false,
 			border: BTreeMap::from([(0, BorderMode::Wrap)]),
+			topology: Topology::Square,
+			neighborhood: Neighborhood::Moore,
 			start_pos: StartingPos::Center,
 			start_dir: 0,
+			start_ants: vec![],
 			ant_limit: ANT_LIMIT,
+			conflict: ConflictStrategy::Hash,
+			random: RandomMode::Shared,
+			watch: vec![],
+			cycles: CycleMode::Off,
+			energy: Default::default(),
 			seed: None,
 			description: "".into(),
 
@@ -90,6 +150,7 @@ impl Default for WorldConfig {
 			start_tick: 0,
 			bg_filter: ByteFilter::Lsb,
 			bg: RenderMask::Tile,
+			bg_plane: 0,
 			fg: RenderMask::Dir,
 			sleep: Some(200),
 
@@ -104,7 +165,7 @@ impl Default for WorldConfig {
 #[cfg_attr(test, derive(ts_rs::TS))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
-pub enum BorderMode { Collide, Despawn, Cycle, Wrap }
+pub enum BorderMode { Collide, Despawn, Cycle, Wrap, Reflect, Klein }
 
 impl TryFrom<String> for BorderMode {
 	type Error = Error;
@@ -115,11 +176,248 @@ impl TryFrom<String> for BorderMode {
 			"die" | "despawn" => Ok(Self::Despawn),
 			"cycle" => Ok(Self::Cycle),
 			"wrap" => Ok(Self::Wrap),
+			"reflect" => Ok(Self::Reflect),
+			"klein" | "mirror" => Ok(Self::Klein),
 			invalid => Err(anyhow!("invalid border mode: '{invalid}'")),
 		}
 	}
 }
 
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Topology {
+	/// square tiles with 8 neighbors
+	Square,
+	/// hexagonal tiles with 6 neighbors, stored in rows where every odd row is shifted right by half a tile
+	Hex,
+}
+
+impl TryFrom<String> for Topology {
+	type Error = Error;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		match value.as_str() {
+			"square" => Ok(Self::Square),
+			"hex" => Ok(Self::Hex),
+			invalid => Err(anyhow!("invalid topology: '{invalid}'")),
+		}
+	}
+}
+
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Neighborhood {
+	/// orthogonal and diagonal neighbors
+	Moore,
+	/// orthogonal neighbors only
+	VonNeumann,
+}
+
+impl TryFrom<String> for Neighborhood {
+	type Error = Error;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		match value.as_str() {
+			"moore" => Ok(Self::Moore),
+			"von_neumann" => Ok(Self::VonNeumann),
+			invalid => Err(anyhow!("invalid neighborhood: '{invalid}'")),
+		}
+	}
+}
+
+/// the directions an ant can face and move in, derived from topology and neighborhood
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lattice {
+	/// square tiles with 8 neighbors
+	Moore,
+	/// square tiles with 4 orthogonal neighbors
+	VonNeumann,
+	/// hexagonal tiles with 6 neighbors
+	Hex,
+}
+
+impl Lattice {
+	/// hex directions clockwise from east, as their closest square direction
+	const HEX_AS_SQUARE: [u8; 6] = [0, 1, 3, 4, 5, 7];
+
+	/// number of directions, i.e. neighbors of a tile
+	#[inline]
+	pub fn dir_count(&self) -> u8 {
+		match self {
+			Self::Moore => Direction::MOD,
+			Self::VonNeumann => 4,
+			Self::Hex => 6,
+		}
+	}
+
+	#[inline]
+	pub fn dirs(&self) -> impl Iterator<Item = Direction> {
+		(0..self.dir_count()).map(Direction::from)
+	}
+
+	/// rotates `dir` clockwise by `by` steps
+	#[inline]
+	pub fn turn(&self, dir: Direction, by: Direction) -> Direction {
+		match self {
+			Self::Moore => dir + by,
+			_ => Direction::from((dir.value() + by.value()) % self.dir_count()),
+		}
+	}
+
+	#[inline]
+	pub fn invert(&self, dir: Direction) -> Direction {
+		match self {
+			Self::Moore => dir.inverted(),
+			_ => Direction::from((dir.value() + self.dir_count() / 2) % self.dir_count()),
+		}
+	}
+
+	/// mirrors `dir` on the x axis (flipping its horizontal component) or on the y axis
+	#[inline]
+	pub fn mirror(&self, dir: Direction, x_axis: bool) -> Direction {
+		let count = self.dir_count();
+		let axis = if x_axis { count / 2 } else { count };
+		Direction::from((axis + count - dir.value() % count) % count)
+	}
+
+	/// position of the neighbor in direction `dir`, which may be out of bounds
+	pub fn neighbor(&self, pos: Pos, dir: Direction) -> Vec2 {
+		let offset = match self {
+			Self::Moore => dir.as_vec(),
+			Self::VonNeumann => self.as_square(dir).as_vec(),
+			Self::Hex => {
+				let (x, y) = match (dir.value(), pos.y % 2 == 1) {
+					(0, _) => (1, 0),
+					(1, false) => (0, 1),
+					(1, true) => (1, 1),
+					(2, false) => (-1, 1),
+					(2, true) => (0, 1),
+					(3, _) => (-1, 0),
+					(4, false) => (-1, -1),
+					(4, true) => (0, -1),
+					(5, false) => (0, -1),
+					(5, true) => (1, -1),
+					_ => panic!("dir overflow"),
+				};
+
+				Vec2 { x, y }
+			}
+		};
+
+		pos.sign() + offset
+	}
+
+	/// closest direction of the 8 square directions
+	#[inline]
+	fn as_square(&self, dir: Direction) -> Direction {
+		match self {
+			Self::Moore => dir,
+			Self::VonNeumann => Direction::from((dir.value() % 4) * 2),
+			Self::Hex => Direction::from(Self::HEX_AS_SQUARE[(dir.value() % 6) as usize]),
+		}
+	}
+
+	/// two characters pointing in direction `dir`
+	pub fn dir_string(&self, dir: Direction) -> String {
+		self.as_square(dir).as_string()
+	}
+}
+
+/// priority of ants competing for the same position.
+/// ants with a set `Dash` pin are preferred by every strategy
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+	/// hash of tick, direction and layer
+	Hash,
+	/// drawn from the world's seeded RNG
+	Random,
+	LowestId,
+	HighestId,
+	/// lowest `birth_tick`
+	Oldest,
+	/// only `Dash`, leaving ties to the processing order
+	Dash,
+}
+
+impl TryFrom<String> for ConflictStrategy {
+	type Error = Error;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		match value.as_str() {
+			"hash" => Ok(Self::Hash),
+			"random" | "rng" => Ok(Self::Random),
+			"lowest_id" => Ok(Self::LowestId),
+			"highest_id" => Ok(Self::HighestId),
+			"oldest" => Ok(Self::Oldest),
+			"dash" => Ok(Self::Dash),
+			invalid => Err(anyhow!("invalid conflict strategy: '{invalid}'")),
+		}
+	}
+}
+
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerOrder {
+	/// highest layer first
+	Descending,
+	/// layer 0 first
+	Ascending,
+}
+
+impl TryFrom<String> for LayerOrder {
+	type Error = Error;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		match value.as_str() {
+			"descending" | "down" => Ok(Self::Descending),
+			"ascending" | "up" => Ok(Self::Ascending),
+			invalid => Err(anyhow!("invalid layer order: '{invalid}'")),
+		}
+	}
+}
+
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RandomMode {
+	/// one RNG stream, drawn from in processing order
+	Shared,
+	/// hash of seed, tick, layer and position, so adding or removing an ant
+	/// does not change the random bits of any other ant
+	Hashed,
+}
+
+impl TryFrom<String> for RandomMode {
+	type Error = Error;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		match value.as_str() {
+			"shared" => Ok(Self::Shared),
+			"hashed" | "local" => Ok(Self::Hashed),
+			invalid => Err(anyhow!("invalid random mode: '{invalid}'")),
+		}
+	}
+}
+
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct StartAnt {
+	/// ID of the ant's behavior
+	pub behavior: u8,
+	/// defaults to `start_pos`
+	pub pos: Option<Pos>,
+	pub layer: u8,
+	pub dir: u8,
+	pub memory: u8,
+}
+
 #[rustfmt::skip]
 #[cfg_attr(test, derive(ts_rs::TS))]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -244,6 +542,8 @@ pub enum RenderMask {
 	None,
 	Tile,
 	Layers,
+	/// field value of each tile
+	Field,
 
 	// ## Ant
 	Dir,
@@ -251,6 +551,7 @@ pub enum RenderMask {
 	BirthTick,
 	InputBits,
 	Mem,
+	Energy,
 }
 
 impl TryFrom<String> for RenderMask {
@@ -261,11 +562,13 @@ impl TryFrom<String> for RenderMask {
 			"none" => Ok(Self::None),
 			"tile" => Ok(Self::Tile),
 			"layers" => Ok(Self::Layers),
+			"field" => Ok(Self::Field),
 			"dir" => Ok(Self::Dir),
 			"id" => Ok(Self::Id),
 			"birth_tick" => Ok(Self::BirthTick),
 			"input_bits" => Ok(Self::InputBits),
 			"mem" => Ok(Self::Mem),
+			"energy" => Ok(Self::Energy),
 
 			invalid => Err(anyhow!("invalid render mask: '{invalid}'")),
 		}
@@ -282,7 +585,94 @@ pub struct MidiConfig {
 	pub offset: BTreeMap<u8, u8>,
 }
 
+/// ants pay energy for each tick and action, gain it from the tiles they stand on,
+/// and die once it runs out.
+/// disabled unless a start energy is set, and behaviors without one never run out
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct EnergyConfig {
+	/// start energy per behavior ID, with ID 0 applying to all behaviors without an entry
+	pub start: BTreeMap<u8, u32>,
+	/// paid by every ant each tick, except on its birth tick
+	pub tick_cost: u32,
+	/// paid for each move
+	pub move_cost: u32,
+	/// paid by the parent for each spawned ant
+	pub spawn_cost: u32,
+	/// paid for each tick in which the ant changes a tile
+	pub write_cost: u32,
+	/// gained each tick per value of the tile the ant stands on
+	pub gain: u32,
+}
+
+impl Default for EnergyConfig {
+	fn default() -> Self {
+		Self {
+			start: BTreeMap::new(),
+			tick_cost: 1,
+			move_cost: 0,
+			spawn_cost: 0,
+			write_cost: 0,
+			gain: 0,
+		}
+	}
+}
+
+impl EnergyConfig {
+	#[inline]
+	pub fn enabled(&self) -> bool {
+		!self.start.is_empty()
+	}
+
+	/// energy of a new ant with the behavior
+	pub fn start_energy(&self, behavior: u8) -> u16 {
+		let start = self.start.get(&behavior).or(self.start.get(&0));
+		start.copied().unwrap_or_default() as u16
+	}
+
+	/// whether ants with the behavior die once they run out of energy
+	#[inline]
+	pub fn starves(&self, behavior: u8) -> bool {
+		self.start.contains_key(&behavior) || self.start.contains_key(&0)
+	}
+}
+
 impl WorldConfig {
+	#[inline]
+	pub fn lattice(&self) -> Lattice {
+		match (self.topology, self.neighborhood) {
+			(Topology::Hex, _) => Lattice::Hex,
+			(Topology::Square, Neighborhood::Moore) => Lattice::Moore,
+			(Topology::Square, Neighborhood::VonNeumann) => Lattice::VonNeumann,
+		}
+	}
+
+	#[inline]
+	pub fn layer_period(&self, layer: u8) -> u32 {
+		self.layer_period.get(&layer).copied().unwrap_or(1)
+	}
+
+	#[inline]
+	pub fn layer_steps(&self, layer: u8) -> u32 {
+		self.layer_steps.get(&layer).copied().unwrap_or(1)
+	}
+
+	/// the layer updates of the tick, in order
+	pub fn layer_schedule(&self, tick: u32) -> Vec<u8> {
+		let layers: Vec<u8> = match self.layer_order {
+			LayerOrder::Descending => (0..self.layers).rev().collect(),
+			LayerOrder::Ascending => (0..self.layers).collect(),
+		};
+
+		layers
+			.into_iter()
+			.filter(|&layer| tick.is_multiple_of(self.layer_period(layer)))
+			.flat_map(|layer| (0..self.layer_steps(layer)).map(move |_| layer))
+			.collect()
+	}
+
 	pub fn validate(&self) -> Result<()> {
 		if self.height < 3 || self.width < 3 {
 			bail!("height / width must not be less than 3")
@@ -299,6 +689,50 @@ impl WorldConfig {
 			bail!("main_layer must not exceed specified max layer")
 		}
 
+		Self::cap(self.planes as u32, "planes", PLANE_CAP as u32)?;
+
+		if self.planes == 0 {
+			bail!("specified plane count must be greater than 0")
+		} else if self.bg_plane >= self.planes {
+			bail!("bg_plane must not exceed specified max plane")
+		}
+
+		for (layer, plane) in &self.plane {
+			if *plane >= self.planes {
+				bail!("plane_{layer} must not exceed specified max plane")
+			}
+		}
+
+		if let Some(plane) = self.tiles.keys().find(|&&plane| plane >= self.planes) {
+			bail!("tiles_{plane} must not exceed specified max plane")
+		}
+
+		for (name, slots) in [
+			("layer_period", &self.layer_period),
+			("layer_steps", &self.layer_steps),
+		] {
+			if let Some(layer) = slots.keys().find(|&&layer| layer >= self.layers) {
+				bail!("{name}_{layer} must not exceed specified max layer")
+			}
+		}
+
+		for (layer, period) in &self.layer_period {
+			if *period == 0 {
+				bail!("layer_period_{layer} must be greater than 0")
+			}
+		}
+
+		for (layer, steps) in &self.layer_steps {
+			if *steps == 0 {
+				bail!("layer_steps_{layer} must be greater than 0")
+			}
+
+			Self::cap(*steps, &format!("layer_steps_{layer}"), LAYER_STEPS_CAP)?;
+		}
+
+		Self::cap(self.diffusion, "diffusion", 0x100)?;
+		Self::cap(self.evaporation, "evaporation", 0x100)?;
+
 		// TODO: limit border slots to max layer count
 		if !self.border.contains_key(&0) {
 			bail!("border_0 needs to be specified")
@@ -310,7 +744,40 @@ impl WorldConfig {
 			bail!("ant_limit must not be less than specified layers")
 		}
 
-		Self::cap(self.start_dir as u32, "start_dir", Direction::MAX as u32)?;
+		Self::cap(
+			self.start_dir as u32,
+			"start_dir",
+			self.lattice().dir_count() as u32 - 1,
+		)?;
+
+		if self.topology == Topology::Hex && self.neighborhood == Neighborhood::VonNeumann {
+			bail!("von_neumann neighborhood is only supported for square topology")
+		}
+
+		for watch in &self.watch {
+			if let WatchCondition::Tile { plane, .. } = watch.condition
+				&& plane >= self.planes
+			{
+				bail!("watched tile plane {plane} must not exceed specified max plane")
+			}
+		}
+
+		let wraps = self
+			.border
+			.values()
+			.any(|mode| matches!(mode, BorderMode::Cycle | BorderMode::Wrap));
+
+		let flips = self.border.values().any(|mode| *mode == BorderMode::Klein);
+
+		// flipping rows would swap the shifted and unshifted ones
+		if self.topology == Topology::Hex && flips {
+			bail!("klein borders are only supported for square topology")
+		}
+
+		// odd heights would shift rows by half a tile when wrapping around vertically
+		if self.topology == Topology::Hex && wraps && self.height % 2 == 1 {
+			bail!("hex worlds with cycle / wrap borders need an even height")
+		}
 
 		if let Some(max_ticks) = self.max_ticks
 			&& self.start_tick > max_ticks
@@ -345,6 +812,18 @@ impl WorldConfig {
 			}
 		}
 
+		let energy = &self.energy;
+
+		for (id, start) in &energy.start {
+			Self::cap(*start, &format!("energy_{id}"), MAX_ENERGY)?;
+		}
+
+		Self::cap(energy.tick_cost, "energy_tick", MAX_ENERGY)?;
+		Self::cap(energy.move_cost, "energy_move", MAX_ENERGY)?;
+		Self::cap(energy.spawn_cost, "energy_spawn", MAX_ENERGY)?;
+		Self::cap(energy.write_cost, "energy_write", MAX_ENERGY)?;
+		Self::cap(energy.gain, "energy_gain", MAX_ENERGY)?;
+
 		for (i, ch) in &self.midi.out_ch {
 			Self::cap(*i as u32, &format!("MIDI Output Slot @{i}"), 3)?;
 			Self::cap(*ch as u32, &format!("MIDI Output Channel @{i}"), 16)?;
@@ -357,9 +836,34 @@ impl WorldConfig {
 		Ok(())
 	}
 
+	/// checks the start ants, which only matter once a world is created from the config
+	pub fn validate_start_ants(&self) -> Result<()> {
+		let mut start_ant_spots = BTreeSet::new();
+
+		for (i, start_ant) in self.start_ants.iter().enumerate() {
+			let pos = start_ant
+				.pos
+				.unwrap_or_else(|| self.start_pos.get(self.height, self.width));
+
+			if start_ant.behavior == 0 {
+				bail!("start ant #{i} needs a behavior ID greater than 0")
+			} else if pos.x >= self.width || pos.y >= self.height {
+				bail!("start ant #{i} is placed outside of the world at {pos:?}")
+			} else if start_ant.layer >= self.layers {
+				bail!("start ant #{i} must not exceed specified max layer")
+			} else if start_ant.dir >= self.lattice().dir_count() {
+				bail!("start ant #{i} has an invalid direction: {}", start_ant.dir)
+			} else if !start_ant_spots.insert((start_ant.layer, pos)) {
+				bail!("start ant #{i} is placed on an occupied position: {pos:?}")
+			}
+		}
+
+		Ok(())
+	}
+
 	#[inline]
 	#[rustfmt::skip]
-	fn cap(number: u32, property: &str, max: u32) -> Result<()> {
+	pub(crate) fn cap(number: u32, property: &str, max: u32) -> Result<()> {
 		if number > max { bail!("[{property}] must not exceed {max}") } Ok(())
 	}
 
@@ -367,3 +871,91 @@ impl WorldConfig {
 		Self::cap(number.unwrap_or_default(), property, max)
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::WorldConfig;
+	use crate::parser::token::Token;
+
+	#[test]
+	fn layer_schedule() {
+		let mut config = WorldConfig {
+			layers: 3,
+			..Default::default()
+		};
+
+		let settings = [
+			("layer_period_1", Token::Number(2)),
+			("layer_steps_0", Token::Number(2)),
+		];
+
+		for (key, value) in settings {
+			config.set_setting(key.into(), value).unwrap();
+		}
+
+		config.validate().unwrap();
+
+		// descending by default
+		assert_eq!(config.layer_schedule(1), [2, 0, 0]);
+		assert_eq!(config.layer_schedule(2), [2, 1, 0, 0]);
+
+		let order = Token::Ident("ascending".into());
+		config.set_setting("layer_order".into(), order).unwrap();
+
+		assert_eq!(config.layer_schedule(3), [0, 0, 2]);
+		assert_eq!(config.layer_schedule(4), [0, 0, 1, 2]);
+	}
+
+	#[test]
+	fn energy_ids_are_decimal() {
+		let mut config = WorldConfig::default();
+		let settings = [
+			("energy", 10),
+			("energy_9", 20),
+			("energy_10", 30),
+			("energy_255", 40),
+		];
+
+		for (key, start) in settings {
+			config
+				.set_setting(key.into(), Token::Number(start))
+				.unwrap();
+		}
+
+		let start = Vec::from_iter(config.energy.start.clone());
+		assert_eq!(start, [(0, 10), (9, 20), (10, 30), (255, 40)]);
+
+		// named as written
+		let energy = Token::Number(70000);
+		let error = config.set_setting("energy_010".into(), energy).unwrap_err();
+		assert!(format!("{error:#}").contains("[energy_010] must not exceed"));
+
+		let energy = Token::Number(1);
+		assert!(config.set_setting("energy_256".into(), energy).is_err());
+	}
+
+	#[test]
+	fn hex_borders() {
+		let validate = |border: &str, height: u32| {
+			let mut config = WorldConfig::default();
+			let settings = [
+				("topology", Token::Ident("hex".into())),
+				("border", Token::Ident(border.into())),
+				("height", Token::Number(height)),
+			];
+
+			for (key, value) in settings {
+				config.set_setting(key.into(), value).unwrap();
+			}
+
+			config.validate()
+		};
+
+		assert!(validate("cycle", 8).is_ok());
+		assert!(validate("cycle", 7).is_err());
+		assert!(validate("wrap", 8).is_ok());
+		assert!(validate("wrap", 7).is_err());
+		assert!(validate("klein", 8).is_err());
+		assert!(validate("reflect", 7).is_ok());
+	}
+}
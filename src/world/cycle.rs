@@ -0,0 +1,304 @@
+use std::{
+	collections::HashMap,
+	fmt::Display,
+	hash::{DefaultHasher, Hash, Hasher},
+};
+
+use anyhow::{Error, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	ant::pin::Pin,
+	world::{World, WorldProperties, config::ConflictStrategy},
+};
+
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CycleMode {
+	Off,
+	/// report the first repeated state
+	Report,
+	/// report the first repeated state and end the simulation
+	End,
+}
+
+impl TryFrom<String> for CycleMode {
+	type Error = Error;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		match value.as_str() {
+			"off" => Ok(Self::Off),
+			"report" => Ok(Self::Report),
+			"end" => Ok(Self::End),
+			invalid => Err(anyhow!("invalid cycle mode: '{invalid}'")),
+		}
+	}
+}
+
+/// a state that repeats, making the simulation periodic from `start` on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Cycle {
+	/// tick of the first occurrence of the state, i.e. the transient length
+	pub start: u32,
+	pub period: u32,
+}
+
+impl Display for Cycle {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let Self { start, period } = self;
+		write!(
+			f,
+			"cycle detected: transient length {start}, period {period}"
+		)
+	}
+}
+
+/// total size of the encoded states kept for comparison, after which no new states are recorded
+const CYCLE_MEMORY: usize = 0x400_0000;
+
+/// first tick and encoded state of each state hash
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(super) struct CycleDetector {
+	seen: HashMap<u64, (u32, Vec<u8>)>,
+	/// total size of the encoded states in `seen`
+	stored: usize,
+	cycle: Option<Cycle>,
+}
+
+impl CycleDetector {
+	/// the random bits, external inputs and hashed conflict priorities of a world
+	/// never repeat, so it can not be checked for cycles
+	pub(super) fn check_world(properties: &WorldProperties) -> Result<()> {
+		let random_inputs = properties.behaviors.values().any(|behavior| {
+			(behavior.inputs.iter()).any(|input| matches!(input.pin, Pin::Random | Pin::Chance))
+		});
+
+		let external_inputs = properties
+			.behaviors
+			.values()
+			.any(|behavior| (behavior.inputs.iter()).any(|input| input.pin == Pin::ExtIn));
+
+		if random_inputs || properties.config.conflict == ConflictStrategy::Random {
+			bail!("cycle detection needs a world without random inputs or conflicts")
+		} else if external_inputs {
+			bail!("cycle detection needs a world without external inputs")
+		} else if properties.config.conflict == ConflictStrategy::Hash {
+			bail!(
+				"cycle detection needs a conflict strategy that does not depend on the tick (not hash)"
+			)
+		}
+
+		Ok(())
+	}
+
+	/// forgets the states recorded after the tick, e.g. after stepping back to it
+	pub(super) fn rewind(&mut self, tick: u32) {
+		self.seen.retain(|_, (first_tick, _)| *first_tick <= tick);
+		self.stored = self.seen.values().map(|(_, state)| state.len()).sum();
+
+		if self
+			.cycle
+			.is_some_and(|cycle| cycle.start + cycle.period > tick)
+		{
+			self.cycle = None;
+		}
+	}
+}
+
+/// collects the bytes fed to it, to compare states exactly after their hashes matched.
+/// finishes with the hash of all collected bytes
+#[derive(Default)]
+struct StateEncoder(Vec<u8>);
+
+impl Hasher for StateEncoder {
+	fn write(&mut self, bytes: &[u8]) {
+		self.0.extend_from_slice(bytes);
+	}
+
+	fn finish(&self) -> u64 {
+		hash_state(&self.0)
+	}
+}
+
+impl World {
+	/// records the current state, detecting the first cycle
+	pub(super) fn check_cycle(&mut self) {
+		if self.cycles.cycle.is_some() {
+			return;
+		}
+
+		let state = self.encode_state();
+		let hash = hash_state(&state);
+		let tick = self.tick_count;
+		let cycles = &mut self.cycles;
+
+		match cycles.seen.get(&hash) {
+			// stepping back revisits states on the very same tick,
+			// and differing states with the same hash are collisions
+			Some((first_tick, first_state)) if *first_tick < tick && *first_state == state => {
+				cycles.cycle = Some(Cycle {
+					start: *first_tick,
+					period: tick - first_tick,
+				});
+			}
+			Some(_) => {}
+			None if cycles.stored + state.len() <= CYCLE_MEMORY => {
+				cycles.stored += state.len();
+				cycles.seen.insert(hash, (tick, state));
+			}
+			None => {}
+		}
+	}
+
+	/// the first detected cycle, if any
+	#[inline]
+	pub fn cycle(&self) -> Option<&Cycle> {
+		self.cycles.cycle.as_ref()
+	}
+
+	/// hash of the simulation state without the RNG, e.g. to compare two runs
+	#[inline]
+	pub fn state_hash(&self) -> u64 {
+		hash_state(&self.encode_state())
+	}
+
+	/// encoding of everything that affects the following ticks.
+	///
+	/// ant ages are only told apart as far as the `Init` pin can, or as far as their order
+	/// matters to `oldest` conflicts, and ant clocks are only included if a behavior reads them.
+	/// layers updating on every n-th tick add their phase
+	fn encode_state(&self) -> Vec<u8> {
+		let mut hasher = StateEncoder::default();
+		let tick = self.tick_count;
+
+		// position of each birth tick among all of them, as later births come after all of them
+		let birth_ranks = (self.config().conflict == ConflictStrategy::Oldest).then(|| {
+			let mut birth_ticks = (self.ants.iter())
+				.flat_map(|(_, ants)| ants.iter().map(|(_, ant)| ant.birth_tick))
+				.collect::<Vec<_>>();
+
+			birth_ticks.sort_unstable();
+			birth_ticks.dedup();
+			birth_ticks
+		});
+
+		let reads_clock = self.properties.behaviors.values().any(|behavior| {
+			(behavior.inputs.iter()).any(|input| matches!(input.pin, Pin::Time | Pin::Pulse))
+		});
+
+		for tiles in &self.planes {
+			tiles.entries.hash(&mut hasher);
+		}
+
+		for decays in &self.tile_decays {
+			decays
+				.remaining(tick)
+				.for_each(|ticks| ticks.hash(&mut hasher));
+		}
+
+		self.field.entries.hash(&mut hasher);
+		self.signal_out.hash(&mut hasher);
+
+		// empty layers too, as ants may spawn into them
+		for layer in 0..self.config().layers {
+			(tick % self.config().layer_period(layer)).hash(&mut hasher);
+		}
+
+		for (layer, ants) in self.ants.iter() {
+			let period = self.config().layer_period(layer);
+			layer.hash(&mut hasher);
+
+			for (pos, ant) in ants.iter() {
+				let data = &ant.data;
+				let age = (tick - ant.birth_tick).min(period);
+
+				(pos.x, pos.y, ant.behavior, ant.dir.value(), ant.memory).hash(&mut hasher);
+				(ant.energy, ant.wait_ticks, age).hash(&mut hasher);
+
+				if reads_clock {
+					ant.clock.hash(&mut hasher);
+				}
+
+				if let Some(birth_ranks) = &birth_ranks {
+					birth_ranks.binary_search(&ant.birth_tick).hash(&mut hasher);
+				}
+
+				let flags = (
+					data.will_halt,
+					data.will_dash,
+					data.will_kill,
+					data.will_die,
+				);
+				(flags, data.will_wait).hash(&mut hasher);
+
+				let child = (
+					data.child_behavior,
+					data.child_layer,
+					data.child_dir.value(),
+				);
+				(child, data.child_memory).hash(&mut hasher);
+			}
+		}
+
+		hasher.0
+	}
+}
+
+fn hash_state(state: &[u8]) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	state.hash(&mut hasher);
+	hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+	use std::hash::{Hash, Hasher};
+
+	use super::{Cycle, StateEncoder, hash_state};
+	use crate::{parser::compiler::compile_world_simple, world::World};
+
+	fn build(settings: &str) -> anyhow::Result<World> {
+		let code = format!("set size = 3; set border = cycle; {settings} ant a = 1 {{ C0 = 1; }}");
+		World::new(compile_world_simple(&code)?)
+	}
+
+	#[test]
+	fn detect_cycle() {
+		let mut world = build("set cycles = end; set conflict = oldest;").unwrap();
+
+		while world.step_ticks(1) > 0 {}
+
+		let cycle = Cycle {
+			start: 3,
+			period: 3,
+		};
+
+		assert_eq!(world.cycle(), Some(&cycle));
+		assert_eq!(world.tick_count(), 6);
+
+		// conflicts are decided by the tick
+		assert!(build("set cycles = report;").is_err());
+		assert!(build("set cycles = report; set conflict = random;").is_err());
+	}
+
+	#[test]
+	fn resume_cycle_detection() {
+		let mut world = build("set cycles = end; set conflict = oldest;").unwrap();
+		world.step_ticks(4);
+
+		let mut resumed = World::from_snapshot(world.snapshot()).unwrap();
+		while resumed.step_ticks(1) > 0 {}
+
+		assert_eq!(resumed.cycle().map(|cycle| cycle.start), Some(3));
+		assert_eq!(resumed.tick_count(), 6);
+	}
+
+	#[test]
+	fn state_encoder_finish() {
+		let mut encoder = StateEncoder::default();
+		(1u8, 2u16, [3u32]).hash(&mut encoder);
+
+		assert_eq!(encoder.finish(), hash_state(&encoder.0));
+	}
+}
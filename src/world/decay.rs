@@ -0,0 +1,195 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use anyhow::{Error, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::util::{
+	grid::Grid,
+	vec2::{Coord, Pos},
+};
+
+/// no pending expiration
+const NEVER: u32 = 0;
+
+/// schedules tiles to be reset once their decay has elapsed
+///
+/// expirations are queued in ascending order, so each tick only has to look at the tiles
+/// expiring on that very tick. each tile keeps a single queue entry: tiles written again
+/// in the meantime are queued again with their latest expiration once their entry comes up,
+/// and cleared tiles drop their entry.
+/// only the expirations are (de)serialized, the queue is rebuilt from them
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(try_from = "Grid<u32>", into = "Grid<u32>")]
+pub struct TileDecays {
+	/// latest expiration tick per tile
+	expirations: Grid<u32>,
+	/// expiration tick of each tile's queue entry
+	queued: Grid<u32>,
+	/// `(expiration, pos)`, earliest expiration first
+	queue: BinaryHeap<Reverse<(u32, Pos)>>,
+}
+
+impl TileDecays {
+	pub fn new(width: Coord, height: Coord) -> Self {
+		Self {
+			expirations: Grid::new(width, height),
+			queued: Grid::new(width, height),
+			queue: BinaryHeap::new(),
+		}
+	}
+
+	/// schedules the tile to expire `decay` ticks after `tick`
+	pub fn schedule(&mut self, pos: Pos, tick: u32, decay: u32) {
+		// ticks end at u32::MAX, so an overflowing expiration never happens anyway
+		let Some(expiration) = tick.checked_add(decay) else {
+			return self.cancel(pos);
+		};
+
+		self.expirations.set(pos, expiration);
+
+		// a later entry is only replaced if the decay was shortened, e.g. on resume
+		let queued = *self.queued.get(pos).unwrap();
+		if queued == NEVER || expiration < queued {
+			self.push(pos, expiration);
+		}
+	}
+
+	#[inline]
+	fn push(&mut self, pos: Pos, expiration: u32) {
+		self.queued.set(pos, expiration);
+		self.queue.push(Reverse((expiration, pos)));
+	}
+
+	#[inline]
+	pub fn dimensions(&self) -> (Coord, Coord) {
+		(self.expirations.width, self.expirations.height)
+	}
+
+	/// ticks until each tile expires, with 0 for tiles that never expire
+	pub fn remaining(&self, tick: u32) -> impl Iterator<Item = u32> {
+		(self.expirations.entries.iter()).map(move |&expiration| expiration.saturating_sub(tick))
+	}
+
+	#[inline]
+	pub fn cancel(&mut self, pos: Pos) {
+		self.expirations.set(pos, NEVER);
+	}
+
+	/// removes and returns the positions of all tiles expiring at (or before) `tick`
+	pub fn expire(&mut self, tick: u32) -> Vec<Pos> {
+		let mut expired = vec![];
+
+		while let Some(&Reverse((queued, pos))) = self.queue.peek()
+			&& queued <= tick
+		{
+			self.queue.pop();
+
+			// replaced by an earlier entry
+			if self.queued.get(pos) != Some(&queued) {
+				continue;
+			}
+
+			self.queued.set(pos, NEVER);
+
+			match *self.expirations.get(pos).unwrap() {
+				NEVER => {}
+				expiration if expiration <= tick => {
+					self.expirations.set(pos, NEVER);
+					expired.push(pos);
+				}
+				expiration => self.push(pos, expiration),
+			}
+		}
+
+		expired
+	}
+}
+
+impl TryFrom<Grid<u32>> for TileDecays {
+	type Error = Error;
+
+	fn try_from(expirations: Grid<u32>) -> Result<Self> {
+		let Grid { width, height, .. } = expirations;
+
+		if expirations.entries.len() != width as usize * height as usize {
+			bail!("tile decays do not match their size");
+		}
+
+		let mut decays = Self::new(width, height);
+
+		for y in 0..height {
+			for x in 0..width {
+				let pos = Pos { x, y };
+				let expiration = *expirations.get(pos).unwrap();
+
+				if expiration != NEVER {
+					decays.expirations.set(pos, expiration);
+					decays.push(pos, expiration);
+				}
+			}
+		}
+
+		Ok(decays)
+	}
+}
+
+impl From<TileDecays> for Grid<u32> {
+	fn from(decays: TileDecays) -> Self {
+		decays.expirations
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::TileDecays;
+	use crate::util::{grid::Grid, vec2::Pos};
+
+	#[test]
+	fn one_entry_per_tile() {
+		let mut decays = TileDecays::new(2, 2);
+		let (a, b) = (Pos { x: 0, y: 0 }, Pos { x: 1, y: 1 });
+
+		for tick in 1..100 {
+			decays.schedule(a, tick, 10);
+		}
+
+		decays.schedule(b, 50, 10);
+		assert_eq!(decays.queue.len(), 2);
+
+		// the first entry of a comes up, but it was written again since
+		assert_eq!(decays.expire(60), [b]);
+		assert_eq!(decays.expire(108), []);
+		assert_eq!(decays.expire(109), [a]);
+		assert!(decays.queue.is_empty());
+
+		// cleared tiles drop their entry
+		decays.schedule(a, 200, 10);
+		decays.cancel(a);
+		assert_eq!(decays.expire(300), []);
+		assert!(decays.queue.is_empty());
+
+		// a shortened decay, e.g. after resuming with another config
+		decays.schedule(a, 300, 100);
+		decays.schedule(b, 310, 100);
+		let mut decays = TileDecays::try_from(Grid::from(decays)).unwrap();
+		decays.schedule(b, 320, 10);
+		assert_eq!(decays.expire(330), [b]);
+		assert_eq!(decays.expire(400), [a]);
+		assert_eq!(decays.expire(410), []);
+		assert!(decays.queue.is_empty());
+	}
+
+	#[test]
+	fn long_decay() {
+		let mut decays = TileDecays::new(2, 2);
+		let pos = Pos { x: 1, y: 1 };
+
+		decays.schedule(pos, 100_000, 70_000);
+		assert_eq!(decays.expire(169_999), []);
+		assert_eq!(decays.expire(170_000), [pos]);
+
+		// expirations past the last tick never happen
+		decays.schedule(pos, u32::MAX - 10, 70_000);
+		assert_eq!(decays.expire(u32::MAX), []);
+	}
+}
@@ -0,0 +1,72 @@
+use std::{
+	collections::BTreeSet,
+	fs::File,
+	io::{self, BufWriter, Write},
+	path::Path,
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::world::observer::{Event, EventKind, Observer};
+
+/// writes the observed events as JSON lines, optionally filtered by kind and behavior
+pub struct EventLog {
+	out: BufWriter<File>,
+	/// event kind names to keep, or all if empty
+	kinds: BTreeSet<String>,
+	/// behavior IDs to keep, or all if empty
+	behaviors: BTreeSet<u8>,
+	/// the first write error, since observers can't return errors
+	error: Option<io::Error>,
+}
+
+impl EventLog {
+	pub fn create(path: &Path, kinds: &[String], behaviors: &[u8]) -> Result<Self> {
+		if let Some(kind) = kinds
+			.iter()
+			.find(|kind| !EventKind::NAMES.contains(&kind.as_str()))
+		{
+			bail!(
+				"invalid event kind: '{kind}'.\n needs to be one of: {}",
+				EventKind::NAMES.join(", ")
+			)
+		}
+
+		let file = File::create(path)
+			.with_context(|| format!("error creating event log '{}'!", path.to_string_lossy()))?;
+
+		Ok(Self {
+			out: BufWriter::new(file),
+			kinds: kinds.iter().cloned().collect(),
+			behaviors: behaviors.iter().copied().collect(),
+			error: None,
+		})
+	}
+
+	fn keeps(&self, event: &Event) -> bool {
+		(self.kinds.is_empty() || self.kinds.contains(event.kind.name()))
+			&& (self.behaviors.is_empty() || self.behaviors.contains(&event.behavior))
+	}
+
+	fn write(&mut self, event: &Event) -> io::Result<()> {
+		serde_json::to_writer(&mut self.out, event)?;
+		writeln!(self.out)
+	}
+}
+
+impl Observer for EventLog {
+	fn event(&mut self, event: &Event) {
+		if self.error.is_none() && self.keeps(event) {
+			self.error = self.write(event).err();
+		}
+	}
+
+	/// flushes the log, returning the first error that occurred while writing it
+	fn finish(&mut self) -> Result<()> {
+		if let Some(error) = self.error.take() {
+			return Err(error).context("error writing event log!");
+		}
+
+		self.out.flush().context("error writing event log!")
+	}
+}
@@ -58,8 +58,17 @@ pub fn compile_world(
 		}
 	}?;
 
-	if properties.name.is_none() && !file_name.starts_with(".") {
-		properties.name = Some(file_name);
+	if !file_name.starts_with(".") {
+		// tile maps are located relative to the world file
+		if let Some(base_dir) = path.parent() {
+			for tiles_path in properties.config.tiles.values_mut() {
+				*tiles_path = base_dir.join(&tiles_path).to_string_lossy().into_owned();
+			}
+		}
+
+		if properties.name.is_none() {
+			properties.name = Some(file_name);
+		}
 	}
 
 	Ok(properties)
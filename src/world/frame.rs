@@ -6,7 +6,9 @@ use crate::{
 	world::{
 		World,
 		config::RenderMask,
-		state::{Ants, WorldStatus},
+		cycle::Cycle,
+		state::{FIELD_SCALE, WorldStatus},
+		watch::WatchEvent,
 	},
 };
 
@@ -22,6 +24,10 @@ pub struct FrameOutput {
 	pub ms: Option<u32>,
 	pub metadata: String, //todo: turn this into a map
 	pub ext_out: Vec<u16>,
+	/// the watch that fired during this frame
+	pub watch_event: Option<WatchEvent>,
+	/// the first detected cycle, if any
+	pub cycle: Option<Cycle>,
 }
 
 impl World {
@@ -32,6 +38,10 @@ impl World {
 	}
 
 	pub fn next_frame(&mut self, input: &FrameInput) -> Option<FrameOutput> {
+		let input = self.inputs.next(input)?;
+		self.history.push(&self.state, &self.watch_event);
+		self.watch_event = None;
+
 		let mut frame_ms = match self.config().fps {
 			Some(0) => panic!(),
 			Some(fps) => Some(1000 / fps),
@@ -73,10 +83,60 @@ impl World {
 						self.status = WorldStatus::Inactive;
 						break;
 					}
+
+					if self.watch_event.is_some() {
+						// paused => the runner continues in stepped mode
+						frame_ms = None;
+						break;
+					}
 				}
 			}
 		}
 
+		Some(self.frame_output(frame_ms))
+	}
+
+	/// advances the simulation by up to `n` ticks without rendering, returning the number of ticks simulated.
+	///
+	/// stops early once the world ends or a watch fires.
+	/// unlike `next_frame`, this ignores `speed`, `start_tick` and `looping`,
+	/// keeps the current external input and is neither recorded nor kept in the history
+	pub fn step_ticks(&mut self, n: u32) -> u32 {
+		if self.is_finished() {
+			return 0;
+		}
+
+		self.status = WorldStatus::Active;
+		self.watch_event = None;
+		self.ext_output.clear();
+
+		for ticks in 1..=n {
+			if !self.tick() {
+				self.status = WorldStatus::Inactive;
+				return ticks;
+			}
+
+			if self.watch_event.is_some() {
+				return ticks;
+			}
+		}
+
+		n
+	}
+
+	/// true once the simulation has ended, i.e. its last tick has been simulated
+	#[inline]
+	pub fn is_finished(&self) -> bool {
+		matches!(self.status, WorldStatus::Inactive)
+	}
+
+	/// renders the current state without advancing the simulation
+	#[inline]
+	pub fn current_frame(&self) -> FrameOutput {
+		self.frame_output(None)
+	}
+
+	fn frame_output(&self, ms: Option<u32>) -> FrameOutput {
 		let fg = self.get_render_values(&self.config().fg);
 		let bg = self.get_render_values(&self.config().bg);
 
@@ -85,13 +145,15 @@ impl World {
 			.map(|(&pos, &value)| (pos, self.config().bg_filter.apply(value)))
 			.collect();
 
-		Some(FrameOutput {
+		FrameOutput {
 			fg,
 			bg,
-			ms: frame_ms,
+			ms,
 			metadata: self.metadata_str(),
 			ext_out: self.ext_output.clone(),
-		})
+			watch_event: self.watch_event.clone(),
+			cycle: self.cycle().copied(),
+		}
 	}
 
 	fn get_render_values(&self, mask: &RenderMask) -> BTreeMap<Pos, u8> {
@@ -99,19 +161,20 @@ impl World {
 			RenderMask::None => Default::default(),
 			RenderMask::Tile => self.tiles_to_map(),
 			RenderMask::Layers => self.layer_occupations(),
+			RenderMask::Field => self.field_to_map(),
 			RenderMask::Dir => self.map_ants(|ant| ant.dir.value()),
 			RenderMask::Id => self.map_ants(|ant| ant.behavior),
 			RenderMask::BirthTick => self.map_ants(|ant| ant.birth_tick as u8),
 			RenderMask::InputBits => self.map_ants(|ant| ant.last_input),
 			RenderMask::Mem => self.map_ants(|ant| ant.memory),
+			RenderMask::Energy => self.map_ants(|ant| ant.energy.min(u8::MAX as u16) as u8),
 		}
 	}
 
 	fn tiles_to_map(&self) -> BTreeMap<Pos, u8> {
 		let width = self.config().width;
 
-		let bg_entries = self
-			.tiles
+		let bg_entries = self.planes[self.config().bg_plane as usize]
 			.entries
 			.iter()
 			.enumerate()
@@ -121,13 +184,26 @@ impl World {
 		BTreeMap::from_iter(bg_entries)
 	}
 
+	fn field_to_map(&self) -> BTreeMap<Pos, u8> {
+		let width = self.config().width;
+
+		self.field
+			.entries
+			.iter()
+			.enumerate()
+			.map(|(i, &value)| (i, (value / FIELD_SCALE) as u8))
+			.filter(|&(_, level)| level != 0)
+			.map(|(i, level)| (Pos::from_index(i, width), level))
+			.collect()
+	}
+
 	fn layer_occupations(&self) -> BTreeMap<Pos, u8> {
 		let mut occupations = BTreeMap::new();
 
 		for (layer, ants) in self.ants.iter() {
 			let new_value = 1u8 << layer;
 
-			for (&pos, _) in ants.iter() {
+			for pos in ants.positions() {
 				occupations
 					.entry(pos)
 					.and_modify(|old_value| *old_value |= new_value)
@@ -140,10 +216,9 @@ impl World {
 
 	fn map_ants(&self, func: impl Fn(&Ant) -> u8) -> BTreeMap<Pos, u8> {
 		self.ants
-			.get(&self.config().main_layer)
-			.unwrap_or(&Ants::new())
+			.layer(self.config().main_layer)
 			.iter()
-			.map(|(&pos, ant)| (pos, func(ant)))
+			.map(|(pos, ant)| (pos, func(ant)))
 			.collect()
 	}
 }
@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+
+use crate::world::{World, state::WorldState, watch::WatchEvent};
+
+/// bounded ring buffer of the states before each of the latest frames
+#[derive(Debug, Clone, Default)]
+pub(super) struct History {
+	capacity: usize,
+	entries: VecDeque<Entry>,
+}
+
+/// the state before a frame, along with the watch that fired in the frame before
+#[derive(Debug, Clone)]
+struct Entry {
+	state: WorldState,
+	watch_event: Option<WatchEvent>,
+}
+
+impl History {
+	pub(super) fn push(&mut self, state: &WorldState, watch_event: &Option<WatchEvent>) {
+		if self.capacity == 0 {
+			return;
+		}
+
+		if self.entries.len() == self.capacity {
+			self.entries.pop_front();
+		}
+
+		self.entries.push_back(Entry {
+			state: state.clone(),
+			watch_event: watch_event.clone(),
+		});
+	}
+}
+
+impl World {
+	/// keeps the states of up to `capacity` past frames to step back to (0 disables the history)
+	pub fn set_history(&mut self, capacity: usize) {
+		let entries = &mut self.history.entries;
+
+		while entries.len() > capacity {
+			entries.pop_front();
+		}
+
+		self.history.capacity = capacity;
+	}
+
+	#[inline]
+	pub fn history_len(&self) -> usize {
+		self.history.entries.len()
+	}
+
+	/// restores the state from before the latest frame.
+	///
+	/// the cycle detector and any samples not taken yet forget the ticks after it.
+	/// returns false if there is no earlier state left to restore
+	pub fn step_back(&mut self) -> bool {
+		let Some(entry) = self.history.entries.pop_back() else {
+			return false;
+		};
+
+		self.state = entry.state;
+		self.watch_event = entry.watch_event;
+		self.inputs.rewind();
+
+		let tick = self.tick_count;
+		self.cycles.rewind(tick);
+
+		if let Some(samples) = &mut self.samples {
+			samples.retain(|sample| sample.tick <= tick);
+		}
+
+		true
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::mpsc::channel;
+
+	use crate::{parser::compiler::compile_world_simple, world::World};
+
+	#[test]
+	fn step_back_and_forth() {
+		let code = "set size = 3; set border = cycle; set conflict = oldest; set cycles = report;
+			set speed = 1; ant a = 1 { C0 = 1; }";
+
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+		let (sender, receiver) = channel();
+		world.set_observer(Box::new(sender));
+		world.set_history(4);
+		world.collect_metrics();
+
+		let play = |world: &mut World| {
+			world.next_frame_auto().unwrap();
+			let events = receiver.try_iter().collect::<Vec<_>>();
+			(world.state_hash(), events)
+		};
+
+		(0..4).for_each(|_| _ = play(&mut world));
+		let tick = world.tick_count();
+		let ahead = (0..3).map(|_| play(&mut world)).collect::<Vec<_>>();
+		assert!(world.cycle().is_some());
+
+		for _ in 0..3 {
+			assert!(world.step_back());
+		}
+
+		assert_eq!(world.tick_count(), tick);
+		assert_eq!(world.cycle(), None);
+
+		let ticks = |world: &mut World| {
+			let samples = world.take_samples();
+			samples.iter().map(|sample| sample.tick).collect::<Vec<_>>()
+		};
+
+		assert_eq!(ticks(&mut world), (1..=tick).collect::<Vec<_>>());
+
+		let again = (0..3).map(|_| play(&mut world)).collect::<Vec<_>>();
+		assert_eq!(again, ahead);
+		assert_eq!(ticks(&mut world), (tick + 1..=tick + 3).collect::<Vec<_>>());
+		assert!(world.cycle().is_some());
+	}
+}
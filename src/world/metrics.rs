@@ -0,0 +1,269 @@
+use std::{
+	collections::BTreeMap,
+	fs::File,
+	io::{BufWriter, Write},
+	mem::take,
+	path::Path,
+};
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+
+use crate::{ant::Ant, world::World};
+
+/// values of the world after a single tick
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Sample {
+	pub tick: u32,
+	/// ant count per behavior ID
+	pub behaviors: BTreeMap<u8, u32>,
+	/// ant count per layer
+	pub layers: Vec<u32>,
+	pub births: u32,
+	pub deaths: u32,
+	/// the ants born during the tick
+	pub lineage: Vec<Lineage>,
+	/// tile count per tile value, per plane
+	pub tiles: Vec<BTreeMap<u8, u32>>,
+	pub signal: u8,
+	/// values emitted via `ExtOut` during the tick
+	pub ext_out: Vec<u16>,
+}
+
+/// an ant and the ant that spawned it
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Lineage {
+	pub serial: u32,
+	pub parent: u32,
+	pub behavior: u8,
+}
+
+/// the values needed to compute a sample, probed before each tick
+#[derive(Debug, Clone, Default)]
+pub(super) struct MetricsProbe {
+	ext_out_len: usize,
+}
+
+/// births and deaths of the current tick, counted where they happen while metrics are collected
+#[derive(Debug, Clone, Default)]
+pub(super) struct Tally {
+	lineage: Vec<Lineage>,
+	deaths: u32,
+}
+
+impl World {
+	/// starts sampling metrics after each tick, to be collected via `take_samples`
+	pub fn collect_metrics(&mut self) {
+		self.samples.get_or_insert_default();
+	}
+
+	/// the samples since the last call
+	#[inline]
+	pub fn take_samples(&mut self) -> Vec<Sample> {
+		self.samples.as_mut().map(take).unwrap_or_default()
+	}
+
+	pub(super) fn metrics_probe(&self) -> MetricsProbe {
+		MetricsProbe {
+			ext_out_len: self.ext_output.len(),
+		}
+	}
+
+	pub(super) fn count_birth(&mut self, ant: &Ant) {
+		if self.samples.is_some() {
+			self.tally.lineage.push(Lineage {
+				serial: ant.serial,
+				parent: ant.parent,
+				behavior: ant.behavior,
+			});
+		}
+	}
+
+	pub(super) fn count_deaths(&mut self, count: usize) {
+		if self.samples.is_some() {
+			self.tally.deaths += count as u32;
+		}
+	}
+
+	pub(super) fn sample_metrics(&mut self, before: &MetricsProbe) {
+		let mut behaviors = BTreeMap::new();
+		let mut layers = vec![];
+
+		for (_, ants) in self.ants.iter() {
+			layers.push(ants.len() as u32);
+
+			for (_, ant) in ants.iter() {
+				*behaviors.entry(ant.behavior).or_default() += 1;
+			}
+		}
+
+		let Tally { lineage, deaths } = take(&mut self.tally);
+
+		let tiles = self
+			.planes
+			.iter()
+			.map(|tiles| {
+				let mut histogram = [0u32; 0x100];
+
+				for &tile in &tiles.entries {
+					histogram[tile as usize] += 1;
+				}
+
+				(0..=u8::MAX)
+					.zip(histogram)
+					.filter(|&(_, count)| count > 0)
+					.collect()
+			})
+			.collect();
+
+		let ext_out = self.ext_output[before.ext_out_len.min(self.ext_output.len())..].to_vec();
+
+		let sample = Sample {
+			tick: self.tick_count,
+			behaviors,
+			layers,
+			births: lineage.len() as u32,
+			deaths,
+			lineage,
+			tiles,
+			signal: self.signal_out,
+			ext_out,
+		};
+
+		if let Some(samples) = &mut self.samples {
+			samples.push(sample);
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricsFormat {
+	/// one `tick,metric,key,value` row per value
+	Csv,
+	/// one JSON object per tick
+	JsonLines,
+}
+
+pub struct MetricsWriter {
+	format: MetricsFormat,
+	out: BufWriter<File>,
+}
+
+impl MetricsWriter {
+	/// creates the file at `path`, choosing the format by its extension
+	pub fn create(path: &Path) -> Result<Self> {
+		let extension = path.extension().unwrap_or_default().to_string_lossy();
+
+		let format = match extension.as_ref() {
+			"csv" => MetricsFormat::Csv,
+			"jsonl" | "ndjson" => MetricsFormat::JsonLines,
+			_ => bail!(
+				"invalid metrics file extension: '{extension}'.\n needs to be either: '.csv', '.jsonl'"
+			),
+		};
+
+		let file = File::create(path).with_context(|| {
+			format!("error creating metrics file '{}'!", path.to_string_lossy())
+		})?;
+
+		let mut writer = Self {
+			format,
+			out: BufWriter::new(file),
+		};
+
+		if format == MetricsFormat::Csv {
+			writeln!(writer.out, "tick,metric,key,value")?;
+		}
+
+		Ok(writer)
+	}
+
+	pub fn write(&mut self, samples: &[Sample]) -> Result<()> {
+		for sample in samples {
+			match self.format {
+				MetricsFormat::Csv => self.write_csv(sample)?,
+				MetricsFormat::JsonLines => {
+					serde_json::to_writer(&mut self.out, sample)?;
+					writeln!(self.out)?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn write_csv(&mut self, sample: &Sample) -> Result<()> {
+		let tick = sample.tick;
+		let out = &mut self.out;
+
+		for (id, count) in &sample.behaviors {
+			writeln!(out, "{tick},behavior,{id},{count}")?;
+		}
+
+		for (layer, count) in sample.layers.iter().enumerate() {
+			writeln!(out, "{tick},layer,{layer},{count}")?;
+		}
+
+		writeln!(out, "{tick},births,,{}", sample.births)?;
+		writeln!(out, "{tick},deaths,,{}", sample.deaths)?;
+
+		for born in &sample.lineage {
+			writeln!(out, "{tick},born,{},{}", born.serial, born.behavior)?;
+			writeln!(out, "{tick},parent,{},{}", born.serial, born.parent)?;
+		}
+
+		for (plane, histogram) in sample.tiles.iter().enumerate() {
+			for (value, count) in histogram {
+				writeln!(out, "{tick},tile_{plane},{value},{count}")?;
+			}
+		}
+
+		writeln!(out, "{tick},signal,,{}", sample.signal)?;
+
+		for value in &sample.ext_out {
+			writeln!(out, "{tick},ext_out,,{value}")?;
+		}
+
+		Ok(())
+	}
+
+	pub fn flush(&mut self) -> Result<()> {
+		self.out.flush().context("error writing metrics file!")
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::Path;
+
+	use super::MetricsWriter;
+	use crate::{parser::compiler::compile_world_simple, world::World};
+
+	#[test]
+	fn count_births_and_deaths() {
+		// the child on layer 1 is killed on the tick it is born
+		let code = "set size = 5; set layers = 2; set layer_order = ascending;
+			ant a = 1 { A1 = 1; AA0 = 1; H = 1; } ant b = 2 { H = 1; } ant k = 3 { ZZ = 1; H = 1; }
+			spawn a { x = 2; y = 2; } spawn k { x = 0; y = 2; layer = 1; }";
+
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+		world.collect_metrics();
+		world.step_ticks(4);
+
+		let samples = world.take_samples();
+		assert_eq!(samples.len(), 4);
+
+		for sample in samples {
+			assert_eq!((sample.births, sample.deaths), (1, 1));
+			assert_eq!(sample.lineage[0].behavior, 2);
+		}
+
+		assert_eq!(world.ants.ant_count(), 2);
+	}
+
+	#[test]
+	fn metrics_formats() {
+		// a `.json` file would not hold valid JSON
+		assert!(MetricsWriter::create(Path::new("metrics.json")).is_err());
+	}
+}
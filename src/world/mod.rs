@@ -1,9 +1,20 @@
 pub mod config;
+pub mod cycle;
+pub mod event_log;
 pub mod file_compiler;
 pub mod frame;
+pub mod metrics;
+pub mod observer;
+pub mod recording;
+pub mod snapshot;
+pub mod tile_map;
+pub mod watch;
 
 use config::WorldConfig;
 
+mod ants;
+mod decay;
+mod history;
 mod state;
 mod tick;
 
@@ -12,15 +23,25 @@ use serde::{Deserialize, Serialize};
 
 use std::{
 	collections::BTreeMap,
+	mem::take,
 	ops::{Deref, DerefMut},
+	path::Path,
 };
 
 use crate::{
-	ant::{Ant, behavior::Behavior},
-	util::dir::Direction,
+	ant::{Ant, behavior::Behavior, pin::Pin},
+	util::{dir::Direction, vec2::Pos},
 	world::{
+		ants::Ants,
 		config::BorderMode,
+		cycle::{CycleDetector, CycleMode},
+		history::History,
+		metrics::{Sample, Tally},
+		observer::Observer,
+		recording::InputLog,
 		state::{WorldState, WorldStatus},
+		tile_map::load_tiles,
+		watch::WatchEvent,
 	},
 };
 
@@ -37,19 +58,134 @@ pub struct WorldProperties {
 	pub config: WorldConfig,
 }
 
-#[derive(Clone)]
+impl WorldProperties {
+	/// checks the config and the behaviors' pins against it
+	fn check(&self) -> Result<()> {
+		let config = &self.config;
+		config.validate()?;
+
+		let dir_count = config.lattice().dir_count();
+
+		for (&id, behavior) in &self.behaviors {
+			if behavior.name.is_empty() {
+				bail!("ant name must not be an empty string (found in ant with id = {id})")
+			}
+
+			let mut pins = behavior.inputs.iter().chain(&behavior.outputs);
+
+			if let Some(pin) = pins.find(|pin| pin.plane >= Some(config.planes)) {
+				bail!(
+					"pin '{}' exceeds the specified plane count (found in ant '{}')",
+					pin.to_ident(),
+					behavior.name
+				)
+			}
+
+			let mut pins = behavior.inputs.iter().chain(&behavior.outputs);
+
+			if let Some(pin) = pins.find(|pin| pin.layer >= Some(config.layers)) {
+				bail!(
+					"pin '{}' exceeds the specified layer count (found in ant '{}')",
+					pin.to_ident(),
+					behavior.name
+				)
+			}
+
+			// directions beyond the lattice's would wrap around
+			if let Some(pin) =
+				(behavior.inputs.iter()).find(|pin| pin.pin.has_dirs() && pin.channel >= dir_count)
+			{
+				bail!(
+					"pin '{}' senses a direction beyond the {dir_count} of the lattice (found in ant '{}')",
+					pin.to_ident(),
+					behavior.name
+				)
+			}
+
+			for pin in [Pin::Dir, Pin::SpawnDir] {
+				if let Some(value) = (behavior.output_values(pin).into_iter())
+					.find(|&value| value >= dir_count as u16)
+				{
+					bail!(
+						"ant '{}' may turn by {value}, beyond the {dir_count} directions of the lattice",
+						behavior.name
+					)
+				}
+			}
+		}
+
+		if config.cycles != CycleMode::Off {
+			CycleDetector::check_world(self)?;
+		}
+
+		Ok(())
+	}
+}
+
 pub struct World {
 	properties: WorldProperties,
 	pub state: WorldState,
+	inputs: InputLog,
+	history: History,
+	/// spare layer storage, reused across ticks
+	scratch: Ants,
+	watch_event: Option<WatchEvent>,
+	/// samples since they were last taken, if metrics are collected
+	samples: Option<Vec<Sample>>,
+	cycles: CycleDetector,
+	/// births and deaths of the current tick, if metrics are collected
+	tally: Tally,
+	observer: Option<Box<dyn Observer + Send>>,
+}
+
+/// clones leave out the observer, which only the original world notifies
+impl Clone for World {
+	fn clone(&self) -> Self {
+		Self {
+			properties: self.properties.clone(),
+			state: self.state.clone(),
+			inputs: self.inputs.clone(),
+			history: self.history.clone(),
+			scratch: self.scratch.clone(),
+			watch_event: self.watch_event.clone(),
+			samples: self.samples.clone(),
+			cycles: self.cycles.clone(),
+			tally: self.tally.clone(),
+			observer: None,
+		}
+	}
 }
 
 impl World {
 	pub fn new(properties: WorldProperties) -> Result<Self> {
-		properties.config.validate()?;
+		let seed = properties.config.seed.unwrap_or_else(rand::random);
+		Self::with_seed(properties, seed)
+	}
+
+	/// like `new`, but seeds the RNG with `seed` instead of the configured seed
+	pub fn with_seed(properties: WorldProperties, seed: u32) -> Result<Self> {
+		properties.check()?;
+		properties.config.validate_start_ants()?;
 
 		let config = properties.config.clone();
 
-		let mut state = WorldState::new(&config);
+		let mut state = WorldState::new(&config, seed);
+
+		for (&plane, path) in &config.tiles {
+			let tiles = load_tiles(Path::new(path))?;
+
+			if tiles.dimensions() != state.planes[plane as usize].dimensions() {
+				bail!(
+					"tile map '{path}' is {}x{}, but the world is {}x{}",
+					tiles.width,
+					tiles.height,
+					config.width,
+					config.height
+				)
+			}
+
+			state.planes[plane as usize] = tiles;
+		}
 
 		if config.start_tick > 0 {
 			state.status = WorldStatus::Active;
@@ -67,30 +203,71 @@ impl World {
 
 		let behaviors = &properties.behaviors;
 
-		for (&id, behavior) in behaviors {
-			if behavior.name.is_empty() {
-				bail!("ant name must not be an empty string (found in ant with id = {id})")
-			}
-		}
+		let Some(&root_id) = behaviors.keys().min() else {
+			bail!("can't run a world with no ants defined")
+		};
 
-		let ant = if let Some(root_id) = behaviors.keys().min() {
-			Ant {
+		if config.start_ants.is_empty() {
+			let ant = Ant {
 				dir: Direction::from(start_dir),
-				behavior: *root_id,
+				behavior: root_id,
+				serial: state.next_serial(),
+				energy: config.energy.start_energy(root_id),
 				..Default::default()
+			};
+
+			state.ants.layer_mut(0).insert(start_pos, ant);
+		}
+
+		for start_ant in &config.start_ants {
+			if !behaviors.contains_key(&start_ant.behavior) {
+				bail!(
+					"start ant uses undefined behavior ID: {}",
+					start_ant.behavior
+				)
 			}
-		} else {
-			bail!("can't run a world with no ants defined")
-		};
 
-		state.ants.entry(0).or_default().insert(start_pos, ant);
+			let ant = Ant {
+				dir: Direction::from(start_ant.dir),
+				behavior: start_ant.behavior,
+				memory: start_ant.memory,
+				serial: state.next_serial(),
+				energy: config.energy.start_energy(start_ant.behavior),
+				..Default::default()
+			};
+
+			let pos = start_ant.pos.unwrap_or(start_pos);
+			state.ants.layer_mut(start_ant.layer).insert(pos, ant);
+		}
 
-		Ok(Self { properties, state })
+		Ok(Self {
+			properties,
+			state,
+			inputs: Default::default(),
+			history: Default::default(),
+			scratch: Ants::new(width, height),
+			watch_event: None,
+			samples: None,
+			cycles: Default::default(),
+			tally: Default::default(),
+			observer: None,
+		})
 	}
 
-	#[inline]
 	pub fn reset(&mut self) {
-		*self = Self::new(self.properties.clone()).unwrap();
+		// unseeded worlds draw their next seed from the RNG,
+		// so that a looping run can be reproduced from its first seed alone
+		let seed = self.config().seed.unwrap_or_else(|| self.state.next_seed());
+		let inputs = take(&mut self.inputs);
+		let history = take(&mut self.history);
+		let samples = take(&mut self.samples);
+		let observer = take(&mut self.observer);
+
+		*self = Self::with_seed(self.properties.clone(), seed).unwrap();
+		self.inputs = inputs;
+		self.history = history;
+		self.samples = samples;
+		self.observer = observer;
 	}
 
 	#[inline]
@@ -108,11 +285,46 @@ impl World {
 		&mut self.properties.config
 	}
 
+	/// the ants of a layer with their positions, ordered by position
+	pub fn layer_ants(&self, layer: u8) -> impl Iterator<Item = (Pos, &Ant)> {
+		self.ants
+			.get(layer)
+			.into_iter()
+			.flat_map(|ants| ants.iter())
+	}
+
+	/// layer, position and fields of the living ant with the serial
+	pub fn find_ant(&self, serial: u32) -> Option<(u8, Pos, &Ant)> {
+		self.ants.iter().find_map(|(layer, ants)| {
+			ants.iter()
+				.find(|(_, ant)| ant.serial == serial)
+				.map(|(pos, ant)| (layer, pos, ant))
+		})
+	}
+
+	/// tile values of a plane, row by row
+	#[inline]
+	pub fn plane_tiles(&self, plane: u8) -> Option<&[u8]> {
+		self.planes
+			.get(plane as usize)
+			.map(|tiles| tiles.entries.as_slice())
+	}
+
+	#[inline]
+	pub fn tile(&self, plane: u8, pos: Pos) -> Option<u8> {
+		self.planes.get(plane as usize)?.get(pos).copied()
+	}
+
 	#[inline]
 	fn get_behavior(&self, id: u8) -> Option<&Behavior> {
 		self.properties.behaviors.get(&id)
 	}
 
+	/// the explicitly addressed plane, or else the plane of the layer
+	fn plane(&self, layer: u8, plane: Option<u8>) -> usize {
+		plane.unwrap_or_else(|| self.config().plane.get(&layer).copied().unwrap_or(0)) as usize
+	}
+
 	fn border_mode(&self, layer: u8) -> BorderMode {
 		self.config()
 			.border
@@ -135,3 +347,35 @@ impl DerefMut for World {
 		&mut self.state
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::World;
+	use crate::parser::compiler::compile_world_simple;
+
+	fn build(settings: &str, body: &str) -> anyhow::Result<World> {
+		World::new(compile_world_simple(&format!(
+			"{settings} ant a = 1 {{ {body} }}"
+		))?)
+	}
+
+	#[test]
+	fn directions_beyond_the_lattice() {
+		let hex = "set topology = hex;";
+		let von_neumann = "set neighborhood = von_neumann;";
+
+		// turning by up to 7
+		assert!(build(hex, "D1 = R0; D2 = R1;").is_err());
+		assert!(build(hex, "AD1 = R0; AD2 = R1; A0 = 1;").is_err());
+
+		// turning by 0, 2 or 4
+		assert!(build(hex, "D1 = R0; D2 = and(R1, -R0);").is_ok());
+		assert!(build(von_neumann, "D2 = R0;").is_err());
+
+		// sensing a seventh or fifth neighbor
+		assert!(build(hex, "C0 = V6;").is_err());
+		assert!(build(von_neumann, "C0 = VC40;").is_err());
+
+		assert!(build("", "D1 = R0; D2 = R1; C0 = V7;").is_ok());
+	}
+}
@@ -0,0 +1,152 @@
+use std::sync::mpsc::Sender;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{ant::Ant, util::vec2::Pos, world::World};
+
+/// receives the events of each tick, e.g. to collect statistics without forking the tick code
+pub trait Observer {
+	fn event(&mut self, event: &Event);
+
+	/// called once the simulation stops, e.g. to flush buffered events
+	fn finish(&mut self) -> Result<()> {
+		Ok(())
+	}
+}
+
+/// something that happened to a single ant
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Event {
+	pub tick: u32,
+	pub layer: u8,
+	/// position of the ant, after moving in case of a move
+	pub pos: Pos,
+	/// behavior ID of the ant
+	pub behavior: u8,
+	/// serial of the ant
+	pub serial: u32,
+	#[serde(flatten)]
+	pub kind: EventKind,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventKind {
+	/// spawned by the ant at `parent`, which may be on another layer
+	Birth {
+		parent: Pos,
+		parent_serial: u32,
+	},
+	Death {
+		cause: DeathCause,
+	},
+	/// killed by the ant at `killer`
+	Kill {
+		killer: Pos,
+	},
+	Move {
+		from: Pos,
+	},
+	/// tried to move, but ran into an obstacle
+	Blocked,
+	/// lost a move or spawn conflict against other ants
+	Conflict,
+}
+
+impl EventKind {
+	pub const NAMES: [&str; 6] = ["birth", "death", "kill", "move", "blocked", "conflict"];
+
+	pub fn name(&self) -> &'static str {
+		let index = match self {
+			Self::Birth { .. } => 0,
+			Self::Death { .. } => 1,
+			Self::Kill { .. } => 2,
+			Self::Move { .. } => 3,
+			Self::Blocked => 4,
+			Self::Conflict => 5,
+		};
+
+		Self::NAMES[index]
+	}
+}
+
+/// forwards the events to a receiver, e.g. on another thread
+impl Observer for Sender<Event> {
+	fn event(&mut self, event: &Event) {
+		// a dropped receiver no longer cares about the events
+		_ = self.send(*event);
+	}
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathCause {
+	/// `Die` pin
+	Die,
+	/// ran out of energy
+	Starve,
+	/// moved across a `despawn` border
+	Despawn,
+}
+
+impl World {
+	/// calls the observer on every following event, replacing any previous observer
+	pub fn set_observer(&mut self, observer: Box<dyn Observer + Send>) {
+		self.observer = Some(observer);
+	}
+
+	/// removes the observer, e.g. to finish it once the simulation stops
+	#[inline]
+	pub fn take_observer(&mut self) -> Option<Box<dyn Observer + Send>> {
+		self.observer.take()
+	}
+
+	#[inline]
+	pub(super) fn observed(&self) -> bool {
+		self.observer.is_some()
+	}
+
+	pub(super) fn emit(&mut self, layer: u8, pos: Pos, ant: &Ant, kind: EventKind) {
+		let tick = self.tick_count;
+
+		if let Some(observer) = &mut self.observer {
+			observer.event(&Event {
+				tick,
+				layer,
+				pos,
+				behavior: ant.behavior,
+				serial: ant.serial,
+				kind,
+			});
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::mpsc::channel;
+
+	use super::EventKind;
+	use crate::{parser::compiler::compile_world_simple, world::World};
+
+	#[test]
+	fn observe_on_another_thread() {
+		let code = "set size = 8; ant a = 1 { A0 = 1; }";
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+
+		let (sender, receiver) = channel();
+		world.set_observer(Box::new(sender));
+
+		std::thread::spawn(move || world.step_ticks(4))
+			.join()
+			.unwrap();
+
+		let births = receiver
+			.iter()
+			.filter(|event| matches!(event.kind, EventKind::Birth { .. }))
+			.count();
+
+		assert!(births > 0);
+	}
+}
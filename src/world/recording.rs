@@ -0,0 +1,141 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::world::{World, WorldProperties, file_compiler::read_file, frame::FrameInput};
+
+/// everything needed to reproduce a run of a world:
+/// the seed that was actually used and the external input of every frame
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Recording {
+	pub seed: u32,
+	/// `ext_in` per call of `next_frame`
+	pub inputs: Vec<u8>,
+}
+
+impl Recording {
+	pub fn save(&self, path: &Path) -> Result<()> {
+		let json = serde_json::to_string(self)?;
+
+		fs::write(path, json)
+			.with_context(|| format!("error writing recording '{}'!", path.to_string_lossy()))
+	}
+
+	pub fn load(path: &Path) -> Result<Self> {
+		let json = read_file(&path.to_path_buf())?;
+
+		serde_json::from_str::<Self>(&json)
+			.with_context(|| format!("invalid recording file '{}'!", path.to_string_lossy()))
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) enum InputLog {
+	#[default]
+	Off,
+	Recording(Recording),
+	/// recording and index of the next frame to replay
+	Replaying(Recording, usize),
+}
+
+impl InputLog {
+	/// records the given input, or replaces it with the recorded one when replaying.
+	///
+	/// returns None if the replay has run out of frames
+	pub(super) fn next(&mut self, input: &FrameInput) -> Option<FrameInput> {
+		match self {
+			Self::Off => Some(input.clone()),
+			Self::Recording(recording) => {
+				recording.inputs.push(input.ext_in);
+				Some(input.clone())
+			}
+			Self::Replaying(recording, frame) => {
+				let ext_in = *recording.inputs.get(*frame)?;
+				*frame += 1;
+				Some(FrameInput { ext_in })
+			}
+		}
+	}
+
+	/// forgets the latest input, e.g. after stepping back a frame
+	pub(super) fn rewind(&mut self) {
+		match self {
+			Self::Off => {}
+			Self::Recording(recording) => _ = recording.inputs.pop(),
+			Self::Replaying(_, frame) => *frame = frame.saturating_sub(1),
+		}
+	}
+}
+
+impl World {
+	/// starts recording the seed and all frame inputs of this world
+	pub fn record(&mut self) -> Result<()> {
+		if self.tick_count() > 0 {
+			bail!("can only record a world from its initial state");
+		}
+
+		self.inputs = InputLog::Recording(Recording {
+			seed: self.seed(),
+			inputs: vec![],
+		});
+
+		Ok(())
+	}
+
+	#[inline]
+	pub fn recording(&self) -> Option<&Recording> {
+		match &self.inputs {
+			InputLog::Recording(recording) => Some(recording),
+			_ => None,
+		}
+	}
+
+	/// creates a world that ignores the input passed to `next_frame`
+	/// and feeds back the recorded inputs instead, ending after the last one
+	pub fn replay(properties: WorldProperties, recording: Recording) -> Result<Self> {
+		let mut world = Self::with_seed(properties, recording.seed)?;
+		world.inputs = InputLog::Replaying(recording, 0);
+		Ok(world)
+	}
+
+	#[inline]
+	pub fn replaying(&self) -> bool {
+		matches!(self.inputs, InputLog::Replaying(..))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		parser::compiler::compile_world_simple,
+		world::{World, frame::FrameInput},
+	};
+
+	#[test]
+	fn replay_reproduces_the_run() {
+		// unseeded, with random and external inputs
+		let code = "set size = 8; set border = wrap; set speed = 2;
+			ant a = 1 { D0 = R0; D1 = xor(K0, R1); A0 = K1; C0 = R2; }";
+		let properties = compile_world_simple(code).unwrap();
+
+		let mut world = World::new(properties.clone()).unwrap();
+		world.record().unwrap();
+
+		for ext_in in 0..10 {
+			world
+				.next_frame(&FrameInput { ext_in: ext_in * 3 })
+				.unwrap();
+		}
+
+		let recording = world.recording().unwrap().clone();
+		let mut replay = World::replay(properties, recording).unwrap();
+
+		// the recorded inputs replace the given ones
+		while replay.next_frame_auto().is_some() {}
+
+		assert_eq!(replay.tick_count(), world.tick_count());
+		assert_eq!(replay.state_hash(), world.state_hash());
+	}
+}
@@ -0,0 +1,192 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::world::{
+	World, WorldProperties, ants::Ants, config::WorldConfig, cycle::CycleDetector,
+	file_compiler::read_file, state::WorldState,
+};
+
+/// needs to be incremented whenever the snapshot layout changes
+pub const SNAPSHOT_VERSION: u32 = 10;
+
+/// the world's properties plus its full mutable state (including RNG state),
+/// so that a resumed run produces the same result as an uninterrupted one
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Snapshot {
+	pub version: u32,
+	pub properties: WorldProperties,
+	pub state: WorldState,
+	/// the states seen so far, if cycles are detected
+	pub(super) cycles: CycleDetector,
+}
+
+impl World {
+	pub fn snapshot(&self) -> Snapshot {
+		Snapshot {
+			version: SNAPSHOT_VERSION,
+			properties: self.properties.clone(),
+			state: self.state.clone(),
+			cycles: self.cycles.clone(),
+		}
+	}
+
+	pub fn from_snapshot(snapshot: Snapshot) -> Result<Self> {
+		let Snapshot {
+			version,
+			properties,
+			state,
+			cycles,
+		} = snapshot;
+
+		if version != SNAPSHOT_VERSION {
+			bail!("unsupported snapshot version: {version} (expected {SNAPSHOT_VERSION})");
+		}
+
+		// the config may have been changed, e.g. to another topology
+		properties.check()?;
+
+		let WorldConfig {
+			width,
+			height,
+			layers,
+			planes,
+			..
+		} = properties.config;
+
+		let wrong_size = state
+			.planes
+			.iter()
+			.any(|tiles| (tiles.width, tiles.height) != (width, height))
+			|| state
+				.tile_decays
+				.iter()
+				.any(|decays| decays.dimensions() != (width, height))
+			|| (state.field.width, state.field.height) != (width, height)
+			|| state.ants.dimensions() != (width, height);
+
+		// tile decays check their entries when deserialized
+		let tile_count = width as usize * height as usize;
+		let wrong_len = (state.planes.iter()).any(|tiles| tiles.entries.len() != tile_count)
+			|| state.field.entries.len() != tile_count;
+
+		if wrong_size {
+			bail!("snapshot state does not match the configured world size");
+		} else if wrong_len {
+			bail!("snapshot state contains a grid with missing or extra tiles");
+		} else if state.ants.layer_count() != layers {
+			bail!("snapshot state does not match the configured layer count");
+		} else if state.planes.len() != planes as usize
+			|| state.tile_decays.len() != planes as usize
+		{
+			bail!("snapshot state does not match the configured plane count");
+		}
+
+		for (_, ants) in state.ants.iter() {
+			if let Some((_, ant)) = ants
+				.iter()
+				.find(|(_, ant)| !properties.behaviors.contains_key(&ant.behavior))
+			{
+				bail!(
+					"snapshot state contains an ant of unknown behavior {}",
+					ant.behavior
+				);
+			}
+		}
+
+		Ok(Self {
+			properties,
+			state,
+			inputs: Default::default(),
+			history: Default::default(),
+			scratch: Ants::new(width, height),
+			watch_event: None,
+			samples: None,
+			cycles,
+			tally: Default::default(),
+			observer: None,
+		})
+	}
+
+	pub fn save_snapshot(&self, path: &Path) -> Result<()> {
+		let json = serde_json::to_string(&self.snapshot())?;
+
+		fs::write(path, json)
+			.with_context(|| format!("error writing snapshot '{}'!", path.to_string_lossy()))
+	}
+
+	pub fn load_snapshot(path: &Path) -> Result<Self> {
+		Self::from_snapshot(Snapshot::load(path)?)
+	}
+}
+
+impl Snapshot {
+	/// reads a snapshot without checking it, e.g. to change its config before resuming
+	pub fn load(path: &Path) -> Result<Self> {
+		let json = read_file(&path.to_path_buf())?;
+
+		serde_json::from_str::<Snapshot>(&json)
+			.with_context(|| format!("invalid snapshot file '{}'!", path.to_string_lossy()))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Snapshot;
+	use crate::{
+		parser::{compiler::compile_world_simple, token::Token},
+		world::World,
+	};
+
+	#[test]
+	fn reject_mismatched_snapshots() {
+		let code = "set size = 4; set layers = 2; set decay = 5; ant a = 1 { C0 = V7; }";
+		let world = World::new(compile_world_simple(code).unwrap()).unwrap();
+
+		// config overrides on resume
+		let mut snapshot = world.snapshot();
+		snapshot.properties.config.layers = 3;
+		assert!(World::from_snapshot(snapshot).is_err());
+
+		let mut snapshot = world.snapshot();
+		snapshot.properties.config.width = 8;
+		assert!(World::from_snapshot(snapshot).is_err());
+
+		// sensing a seventh neighbor of six
+		let mut snapshot = world.snapshot();
+		let config = &mut snapshot.properties.config;
+		let topology = Token::Ident("hex".into());
+		config.set_setting("topology".into(), topology).unwrap();
+		assert!(World::from_snapshot(snapshot).is_err());
+
+		// tampered snapshots
+		let mut snapshot = world.snapshot();
+		snapshot.properties.behaviors.clear();
+		assert!(World::from_snapshot(snapshot).is_err());
+
+		let json = serde_json::to_value(world.snapshot()).unwrap();
+		let resume = |json: &serde_json::Value| {
+			World::from_snapshot(serde_json::from_value::<Snapshot>(json.clone())?)
+		};
+		assert!(resume(&json).is_ok());
+
+		for grid in ["/state/planes/0", "/state/tile_decays/0", "/state/field"] {
+			let mut json = json.clone();
+			let entries = json.pointer_mut(&format!("{grid}/entries")).unwrap();
+			entries.as_array_mut().unwrap().pop();
+			assert!(resume(&json).is_err());
+		}
+
+		let mut json = json;
+		json["state"]["ants"]["layers"][0][0][0]["x"] = 9.into();
+		assert!(serde_json::from_value::<Snapshot>(json.clone()).is_err());
+
+		let ants = &mut json["state"]["ants"]["layers"][0];
+		ants[0][0]["x"] = 0.into();
+		let ant = ants[0].clone();
+		ants.as_array_mut().unwrap().push(ant);
+		assert!(serde_json::from_value::<Snapshot>(json).is_err());
+	}
+}
@@ -1,21 +1,27 @@
-use std::{
-	collections::BTreeMap,
-	ops::{Deref, DerefMut},
-};
-
 use crate::{
 	ant::Ant,
-	util::{grid::Grid, vec2::Pos},
-	world::config::WorldConfig,
+	util::{
+		grid::Grid,
+		hash_u32,
+		vec2::{Coord, Pos},
+	},
+	world::{ants::Ants, config::WorldConfig, decay::TileDecays},
 };
-use rand::{RngExt, SeedableRng, rngs::SmallRng};
+use anyhow::{Error, Result, bail};
+use rand::{RngExt, SeedableRng, rngs::Xoshiro256PlusPlus};
+use serde::{Deserialize, Serialize};
+
+/// same algorithm as `SmallRng` on 64-bit platforms, but (de)serializable and portable
+type WorldRng = Xoshiro256PlusPlus;
 
 pub type Tile = u8;
 
 pub type Tiles = Grid<Tile>;
-pub type Ants = BTreeMap<Pos, Ant>;
 
-#[derive(Clone, Default, Debug)]
+/// field values are fixed point numbers, with ants reading and depositing whole units
+pub const FIELD_SCALE: u16 = 0x100;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub enum WorldStatus {
 	#[default]
 	Init,
@@ -23,13 +29,20 @@ pub enum WorldStatus {
 	Inactive,
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct WorldState {
-	rng: Option<SmallRng>,
+	seed: u32,
+	rng: Option<WorldRng>,
 	pub(super) tick_count: u32,
+	/// serial of the latest ant
+	pub(super) last_serial: u32,
 	pub(super) status: WorldStatus,
-	pub tiles: Tiles,
-	pub tile_decays: BTreeMap<Pos, u16>,
+	/// one tile grid per plane
+	pub planes: Vec<Tiles>,
+	/// one decay schedule per plane
+	pub tile_decays: Vec<TileDecays>,
+	/// diffusing scalar field, shared by all layers
+	pub field: Grid<u16>,
 	pub ants: Layers,
 	pub signal_in: u8,
 	pub signal_out: u8,
@@ -38,22 +51,34 @@ pub struct WorldState {
 }
 
 impl WorldState {
-	pub(super) fn new(config: &WorldConfig) -> Self {
-		let tiles = Grid::new(config.width, config.height);
-
-		let rng = if let Some(seed) = config.seed {
-			Some(SmallRng::seed_from_u64(seed as u64))
-		} else {
-			Some(SmallRng::from_seed(rand::random::<[u8; 32]>()))
-		};
+	pub(super) fn new(config: &WorldConfig, seed: u32) -> Self {
+		let planes = (0..config.planes)
+			.map(|_| Grid::new(config.width, config.height))
+			.collect();
+		let tile_decays = (0..config.planes)
+			.map(|_| TileDecays::new(config.width, config.height))
+			.collect();
+		let field = Grid::new(config.width, config.height);
+		let ants = Layers::new(config.layers, config.width, config.height);
+		let rng = Some(WorldRng::seed_from_u64(seed as u64));
 
 		Self {
-			tiles,
+			planes,
+			tile_decays,
+			field,
+			ants,
+			seed,
 			rng,
 			..Default::default()
 		}
 	}
 
+	/// the seed the RNG was initialized with (either configured or random)
+	#[inline]
+	pub fn seed(&self) -> u32 {
+		self.seed
+	}
+
 	#[inline]
 	pub fn tick_count(&self) -> u32 {
 		self.tick_count
@@ -64,13 +89,40 @@ impl WorldState {
 		self.rng.as_mut().expect("rng must be Some").random()
 	}
 
-	pub(super) fn tile_decay(&mut self) {
-		let current_tick = self.tick_count as u16;
+	#[inline]
+	pub(super) fn next_seed(&mut self) -> u32 {
+		self.rng.as_mut().expect("rng must be Some").random()
+	}
 
-		for (pos, expiration) in self.tile_decays.clone() {
-			if current_tick == expiration {
-				self.tiles.set(pos, 0);
-				self.tile_decays.remove(&pos);
+	#[inline]
+	pub(super) fn next_serial(&mut self) -> u32 {
+		self.last_serial += 1;
+		self.last_serial
+	}
+
+	/// hash of the seed, the current tick and the keys, independent of any RNG draws
+	pub(super) fn hashed_seed(&self, keys: &[u32]) -> u32 {
+		let mut hash = hash_u32(self.seed ^ hash_u32(self.tick_count));
+
+		for key in keys {
+			hash = hash_u32(hash ^ hash_u32(*key));
+		}
+
+		hash
+	}
+
+	/// 8 random bits for the `draw`-th draw of the ant at the position, in the layer's `step`-th update
+	#[inline]
+	pub(super) fn hashed_rng(&self, layer: u8, step: u32, pos: Pos, draw: u32) -> u8 {
+		let pos_key = ((pos.y as u32) << 16) | pos.x as u32;
+		let layer_key = (step << 8) | layer as u32;
+		(self.hashed_seed(&[layer_key, pos_key, draw]) >> 24) as u8
+	}
+
+	pub(super) fn tile_decay(&mut self) {
+		for (tiles, decays) in self.planes.iter_mut().zip(&mut self.tile_decays) {
+			for pos in decays.expire(self.tick_count) {
+				tiles.set(pos, 0);
 			}
 		}
 	}
@@ -105,30 +157,151 @@ impl WorldState {
 	}
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct Layers(LayerContainer);
-type LayerContainer = BTreeMap<u8, Ants>;
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "LayersJSON", into = "LayersJSON")]
+pub struct Layers {
+	width: Coord,
+	height: Coord,
+	layers: Vec<Ants>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayersJSON {
+	width: Coord,
+	height: Coord,
+	layers: Vec<Vec<(Pos, Ant)>>,
+}
+
+impl TryFrom<LayersJSON> for Layers {
+	type Error = Error;
+
+	fn try_from(value: LayersJSON) -> Result<Self> {
+		let Ok(layer_count) = u8::try_from(value.layers.len()) else {
+			bail!("too many layers: {}", value.layers.len());
+		};
+
+		let mut layers = Self::new(layer_count, value.width, value.height);
+
+		for (layer, ants) in value.layers.into_iter().enumerate() {
+			let layer_ants = layers.layer_mut(layer as u8);
+
+			for (pos, ant) in ants {
+				if pos.x >= value.width || pos.y >= value.height {
+					bail!("ant position out of bounds: {pos:?}");
+				} else if layer_ants.append(pos, ant).is_some() {
+					bail!("multiple ants at the same position: {pos:?}");
+				}
+			}
+
+			layer_ants.sort();
+		}
+
+		Ok(layers)
+	}
+}
+
+impl From<Layers> for LayersJSON {
+	fn from(value: Layers) -> Self {
+		Self {
+			width: value.width,
+			height: value.height,
+			layers: value
+				.layers
+				.iter()
+				.map(|ants| ants.iter().map(|(pos, ant)| (pos, *ant)).collect())
+				.collect(),
+		}
+	}
+}
 
 impl Layers {
+	pub fn new(layer_count: u8, width: Coord, height: Coord) -> Self {
+		Self {
+			width,
+			height,
+			layers: (0..layer_count).map(|_| Ants::new(width, height)).collect(),
+		}
+	}
+
+	#[inline]
+	pub fn layer(&self, layer: u8) -> &Ants {
+		&self.layers[layer as usize]
+	}
+
+	#[inline]
 	pub fn layer_mut(&mut self, layer: u8) -> &mut Ants {
-		self.get_mut(&layer).unwrap()
+		&mut self.layers[layer as usize]
+	}
+
+	#[inline]
+	pub fn get(&self, layer: u8) -> Option<&Ants> {
+		self.layers.get(layer as usize)
+	}
+
+	/// iterates over all layers with their index
+	pub fn iter(&self) -> impl Iterator<Item = (u8, &Ants)> {
+		self.layers
+			.iter()
+			.enumerate()
+			.map(|(layer, ants)| (layer as u8, ants))
+	}
+
+	#[inline]
+	pub fn layer_count(&self) -> u8 {
+		self.layers.len() as u8
+	}
+
+	#[inline]
+	pub fn dimensions(&self) -> (Coord, Coord) {
+		(self.width, self.height)
 	}
 
 	pub fn ant_count(&self) -> usize {
-		self.values().map(|layer| layer.len()).sum()
+		self.layers.iter().map(|layer| layer.len()).sum()
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.layers.iter().all(|layer| layer.is_empty())
 	}
 }
 
-impl Deref for Layers {
-	type Target = LayerContainer;
+#[cfg(test)]
+mod test {
+	use crate::{parser::compiler::compile_world_simple, util::vec2::Pos, world::World};
 
-	fn deref(&self) -> &Self::Target {
-		&self.0
+	/// random memories of the ant at (3, 3), with or without an ant iterated before it
+	fn memories(random: &str, ant_before: bool) -> Vec<u8> {
+		let before = if ant_before {
+			"spawn a { x = 0; y = 0; }"
+		} else {
+			""
+		};
+		let code = format!(
+			"set size = 5; set seed = 5; set random = {random};
+			ant a = 1 {{ H = 1; M0 = R0; M1 = R1; M2 = R2; M3 = R3; }}
+			{before} spawn a {{ x = 3; y = 3; }}"
+		);
+
+		let mut world = World::new(compile_world_simple(&code).unwrap()).unwrap();
+		let pos = Pos { x: 3, y: 3 };
+
+		(0..8)
+			.map(|_| {
+				world.step_ticks(1);
+				let (_, ant) = world
+					.layer_ants(0)
+					.find(|&(ant_pos, _)| ant_pos == pos)
+					.unwrap();
+				ant.memory
+			})
+			.collect()
 	}
-}
 
-impl DerefMut for Layers {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.0
+	#[test]
+	fn hashed_random_bits_ignore_other_ants() {
+		assert_eq!(memories("hashed", true), memories("hashed", false));
+		assert_ne!(memories("shared", true), memories("shared", false));
 	}
 }
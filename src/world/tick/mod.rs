@@ -1,5 +1,6 @@
-use crate::world::World;
+use crate::world::{World, cycle::CycleMode, watch::WatchAction};
 mod tick_async;
+mod tick_field;
 mod tick_sync;
 mod tick_util;
 
@@ -10,8 +11,18 @@ impl World {
 			return false;
 		}
 
+		let cycles = self.config().cycles;
+
+		if cycles != CycleMode::Off && self.tick_count == 0 {
+			// the initial state may be part of the cycle as well
+			self.check_cycle();
+		}
+
 		self.tick_count += 1;
 
+		let watch_probe = (!self.config().watch.is_empty()).then(|| self.watch_probe());
+		let metrics_probe = self.samples.is_some().then(|| self.metrics_probe());
+
 		// signals
 		self.signal_in = self.signal_out;
 		self.signal_out = 0;
@@ -21,18 +32,47 @@ impl World {
 			self.tile_decay();
 		}
 
+		// field diffusion / evaporation
+		if self.config().diffusion > 0 || self.config().evaporation > 0 {
+			self.field_tick();
+		}
+
 		// ants
-		for layer in (0..self.config().layers).rev() {
-			if self.ants.get(&layer).is_some() {
-				self.tick_layer(layer);
+		let schedule = self.config().layer_schedule(self.tick_count);
+
+		for (i, &layer) in schedule.iter().enumerate() {
+			let step = schedule[..i]
+				.iter()
+				.filter(|&&other| other == layer)
+				.count() as u32;
+
+			if !self.ants.layer(layer).is_empty() {
+				self.tick_layer(layer, step, &schedule[i + 1..]);
 			}
 		}
 
-		self.ants.retain(|_, ants| !ants.is_empty());
+		if let Some(watch_probe) = watch_probe {
+			self.check_watches(&watch_probe);
+		}
+
+		if let Some(metrics_probe) = metrics_probe {
+			self.sample_metrics(&metrics_probe);
+		}
+
+		if cycles != CycleMode::Off {
+			self.check_cycle();
+		}
 
 		// end world if conditions are met
 		let no_ants = self.ants.is_empty();
 
+		let watch_end = self
+			.watch_event
+			.as_ref()
+			.is_some_and(|event| event.watch.action == WatchAction::End);
+
+		let cycle_end = cycles == CycleMode::End && self.cycle().is_some();
+
 		let tick_overflow = self.tick_count == u32::MAX;
 
 		let max_tick = self
@@ -41,17 +81,22 @@ impl World {
 			.map(|max| self.tick_count >= max)
 			.unwrap_or_default();
 
-		!(no_ants || tick_overflow || max_tick)
+		!(no_ants || tick_overflow || max_tick || watch_end || cycle_end)
 	}
 
-	fn tick_layer(&mut self, layer: u8) {
+	/// runs the `step`-th update of the layer this tick, before the `pending` ones
+	fn tick_layer(&mut self, layer: u8, step: u32, pending: &[u8]) {
 		// tick ants (sync)
 		let mut all_outputs = vec![];
 
-		for (pos, ant) in self.ants[&layer].clone() {
+		let positions = self.ants.layer(layer).positions().collect::<Vec<_>>();
+
+		for pos in positions {
+			let ant = self.ants.layer(layer).get(&pos).copied().unwrap();
+
 			if !ant.waiting() {
-				let input = self.get_input(&ant, pos, layer);
-				let output = self.get_output(&ant, input);
+				let input = self.get_input(&ant, pos, layer, step);
+				let output = self.get_output(&ant, layer, input);
 				all_outputs.push((pos, input, output));
 			}
 		}
@@ -63,7 +108,142 @@ impl World {
 		// tick ants (async)
 		self.kill_tick(layer);
 		self.move_tick(layer);
-		self.spawn_tick(layer);
+		self.spawn_tick(layer, pending);
 		self.end_tick(layer);
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		parser::compiler::compile_world_simple,
+		util::{dir::Direction, hash_u32, vec2::Pos},
+		world::{World, observer::EventKind},
+	};
+
+	/// checksum of the rendered frames and the final ant count
+	fn play(code: &str, frames: usize) -> (u32, usize) {
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+		let mut checksum = 0;
+
+		for _ in 0..frames {
+			let Some(frame) = world.next_frame_auto() else {
+				break;
+			};
+
+			for (pos, value) in frame.fg.iter().chain(&frame.bg) {
+				let key = ((pos.x as u32) << 20) | ((pos.y as u32) << 8) | *value as u32;
+				checksum = hash_u32(checksum ^ key);
+			}
+		}
+
+		(checksum, world.ants.ant_count())
+	}
+
+	/// the results of the `BTreeMap<Pos, Ant>` layer storage, before it was replaced
+	#[test]
+	fn same_results_as_btree_map() {
+		let chains = "set size = 20; set seed = 7; set border = wrap; set ant_limit = 400; set layers = 2;
+			ant a = 1 { D0 = R0; D1 = R1; C0 = -C0; A0 = and(R2, -C0); AA0 = R3; Z = and(RR7, VA0); DD = R4; }";
+
+		let crowd = "set size = 12; set seed = 3; set border = collide; set ant_limit = 200;
+			ant a = 1 { D0 = R0; D1 = R1; D2 = R2; A0 = RR0; H = and(R3, R4, R5); C0 = V0; }";
+
+		assert_eq!(play(chains, 300), (0x49994fac, 400));
+		assert_eq!(play(crowd, 300), (0xb4c4167e, 144));
+	}
+
+	#[test]
+	fn newborns_wait_for_their_first_update() {
+		// dies if `Init` is read on any update but the first one
+		let code = "set size = 16; set border = cycle; set ant_limit = 60; set layer_steps = 3;
+			ant a = 1 { A0 = 1; Z = and(J, or(T0, T1)); }";
+
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+		world.collect_metrics();
+		world.step_ticks(20);
+
+		let samples = world.take_samples();
+		assert!(samples.iter().map(|sample| sample.births).sum::<u32>() > 10);
+		assert_eq!(samples.iter().map(|sample| sample.deaths).sum::<u32>(), 0);
+
+		let tick = world.tick_count();
+		let mut newborns = world
+			.layer_ants(0)
+			.filter(|(_, ant)| ant.birth_tick == tick);
+		assert!(newborns.all(|(_, ant)| ant.clock == 0));
+	}
+
+	#[test]
+	fn explicit_and_layer_planes_share_masks() {
+		// the layer's plane is set before the plane count, and addressed both ways
+		let code = "set size = 3; set plane = 1; set planes = 2;
+			ant a = 1 { CC = 1; C0 = 1; C1_1 = 1; H = 1; }";
+
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+		world.step_ticks(1);
+
+		let (pos, _) = world.layer_ants(0).next().unwrap();
+		assert_eq!(world.tile(1, pos), Some(0b11));
+		assert_eq!(world.tile(0, pos), Some(0));
+	}
+
+	#[test]
+	fn behaviors_without_start_energy_never_starve() {
+		let code = "set size = 5; set energy_1 = 4;
+			ant a = 1 { A1 = 1; H = 1; } ant b = 2 { H = 1; }";
+
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+		world.step_ticks(10);
+
+		let behaviors = world.layer_ants(0).map(|(_, ant)| ant.behavior);
+		assert_eq!(behaviors.collect::<Vec<_>>(), [2]);
+	}
+
+	#[test]
+	fn children_link_to_their_parents() {
+		// a row of halted ants, each spawning the next one behind it
+		let code = "set size = 5; set border = collide;
+			ant a = 1 { A0 = 1; H = 1; } spawn a { x = 3; y = 2; }";
+
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+		let (sender, receiver) = std::sync::mpsc::channel();
+		world.set_observer(Box::new(sender));
+		world.step_ticks(6);
+
+		for (serial, x) in (1..=4).zip((0..=3).rev()) {
+			let (_, pos, ant) = world.find_ant(serial).unwrap();
+			assert_eq!((pos.x, pos.y), (x, 2));
+			assert_eq!(ant.parent, serial - 1);
+		}
+
+		assert_eq!(world.ants.ant_count(), 4);
+
+		let births = receiver.try_iter().filter_map(|event| match event.kind {
+			EventKind::Birth { parent_serial, .. } => Some((parent_serial, event.serial)),
+			_ => None,
+		});
+
+		assert_eq!(births.collect::<Vec<_>>(), [(1, 2), (2, 3), (3, 4)]);
+	}
+
+	#[test]
+	fn hex_rows_wrap_to_the_same_parity() {
+		let step = |topology: &str, (x, y), dir| {
+			let code = format!("set size = 4; set topology = {topology}; ant a = 1 {{ H = 1; }}");
+			let world = World::new(compile_world_simple(&code).unwrap()).unwrap();
+			let (pos, _) = world
+				.next_step(Pos { x, y }, 0, Direction::from(dir))
+				.unwrap();
+			(pos.x, pos.y)
+		};
+
+		assert_eq!(step("square", (3, 1), 0), (0, 2));
+		assert_eq!(step("hex", (3, 1), 0), (0, 3));
+		assert_eq!(step("hex", (3, 3), 0), (0, 1));
+		assert_eq!(step("hex", (0, 2), 3), (3, 0));
+
+		// south-east from the last odd row, crossing both borders
+		assert_eq!(step("hex", (3, 3), 1), (1, 0));
+	}
+}
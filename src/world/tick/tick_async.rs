@@ -1,42 +1,87 @@
-use std::{
-	collections::{BTreeMap, BTreeSet},
-	mem::swap,
-};
+use std::{collections::BTreeMap, mem::take};
 
 use crate::{
 	ant::Ant,
-	util::vec2::Pos,
-	world::{World, config::BorderMode, state::Ants},
+	util::{dir::Direction, vec2::Pos},
+	world::{
+		World,
+		ants::Ants,
+		config::BorderMode,
+		observer::{DeathCause, EventKind},
+	},
 };
 
 enum MoveAction {
 	Stay,
-	Move(Pos),
+	/// stay, after failing to move
+	Blocked,
+	/// stay, after losing a conflict
+	Lost,
+	/// move to the position, facing the direction, which borders may have changed
+	Move(Pos, Direction),
+	/// leave the world across a `despawn` border
+	Despawn,
 	Nop,
 }
 
 impl World {
 	pub(super) fn kill_tick(&mut self, layer: u8) {
-		let mut kills = BTreeSet::new();
+		// killed position => killer position
+		let mut kills = BTreeMap::new();
 
-		for (pos, ant) in &self.ants[&layer].clone() {
+		for (pos, ant) in self.ants.layer(layer).iter() {
 			if ant.will_kill
 				&& !ant.waiting()
-				&& let Some(next_pos) = self.next_pos(*pos, layer, ant.dir)
-				&& self.ants[&layer].contains_key(&next_pos)
+				&& let Some(next_pos) = self.next_pos(pos, layer, ant.dir)
+				&& self.ants.layer(layer).contains_key(&next_pos)
 			{
-				kills.insert(next_pos);
+				kills.entry(next_pos).or_insert(pos);
 			}
 		}
 
+		if self.observed() {
+			for (&pos, &killer) in &kills {
+				let ant = *self.ants.layer(layer).get(&pos).unwrap();
+				self.emit(layer, pos, &ant, EventKind::Kill { killer });
+			}
+		}
+
+		self.count_deaths(kills.len());
+
 		self.ants
 			.layer_mut(layer)
-			.retain(|pos, _| !kills.contains(pos));
+			.retain(|pos, _| !kills.contains_key(pos));
 	}
 
 	pub(super) fn end_tick(&mut self, layer: u8) {
+		// energy
+		if self.config().energy.enabled() {
+			self.energy_tick(layer);
+		}
+
 		// die
-		self.ants.layer_mut(layer).retain(|_, ant| !ant.will_die);
+		let energy = &self.properties.config.energy;
+
+		let deaths = (self.state.ants.layer(layer).iter())
+			.filter_map(|(pos, ant)| {
+				let cause = if ant.will_die {
+					DeathCause::Die
+				} else if ant.energy == 0 && energy.starves(ant.behavior) {
+					DeathCause::Starve
+				} else {
+					return None;
+				};
+
+				Some((pos, *ant, cause))
+			})
+			.collect::<Vec<_>>();
+
+		self.count_deaths(deaths.len());
+
+		for (pos, ant, cause) in deaths {
+			self.emit(layer, pos, &ant, EventKind::Death { cause });
+			self.ants.layer_mut(layer).remove(&pos);
+		}
 
 		// wait
 		for ant in &mut self.ants.layer_mut(layer).values_mut() {
@@ -48,13 +93,38 @@ impl World {
 		}
 	}
 
+	/// applies the tile gains and tick costs of the layer's ants
+	fn energy_tick(&mut self, layer: u8) {
+		let tick = self.tick_count;
+		let plane = self.plane(layer, None);
+		let energy = &self.properties.config.energy;
+		let tiles = &self.state.planes[plane];
+
+		for (pos, ant) in self.state.ants.layer_mut(layer).iter_mut() {
+			if ant.birth_tick == tick {
+				continue;
+			}
+
+			let gain = energy.gain * *tiles.get(pos).unwrap() as u32;
+			let new_energy = (ant.energy as u32 + gain).saturating_sub(energy.tick_cost);
+			ant.energy = new_energy.min(u16::MAX as u32) as u16;
+		}
+	}
+
 	pub(super) fn move_tick(&mut self, layer: u8) {
-		let mut source = Ants::new();
-		let mut result = Ants::new();
+		let mut source = take(self.ants.layer_mut(layer));
+		let mut result = take(&mut self.scratch);
+
+		// arena index of the next ant to start a chain from
+		let mut cursor = 0;
 
-		swap(self.ants.layer_mut(layer), &mut source);
+		let salt = self.conflict_salt();
+		let move_cost = self.config().energy.move_cost as u16;
+
+		while let Some((next, pos, ant)) = source.next_from(cursor) {
+			cursor = next;
+			source.remove(&pos);
 
-		while let Some((pos, ant)) = source.pop_first() {
 			let mut stack = vec![(pos, ant)];
 
 			// used to resolve cycles
@@ -69,21 +139,21 @@ impl World {
 						cycle_pos = None;
 					}
 
-					let target_pos = self
-						.next_pos(pos, layer, ant.dir)
+					let (target_pos, target_dir) = self
+						.next_step(pos, layer, ant.dir)
 						.expect("no target position for ant in cycle");
 
 					// all ants in cycle can move
-					MoveAction::Move(target_pos)
-				} else if let Some(target_pos) = self.next_pos(pos, layer, ant.dir) {
+					MoveAction::Move(target_pos, target_dir)
+				} else if let Some((target_pos, target_dir)) = self.next_step(pos, layer, ant.dir) {
 					if result.contains_key(&target_pos) {
 						// target pos is occupied in result => can't move
-						MoveAction::Stay
+						MoveAction::Blocked
 					} else if let Some(&target_ant) = source.get(&target_pos) {
 						// target pos is occupied in source
 						if target_ant.halted() {
 							// dead end => stay
-							MoveAction::Stay
+							MoveAction::Blocked
 						} else {
 							// chain => recurse
 							stack.push((pos, ant));
@@ -98,36 +168,58 @@ impl World {
 							// target is already part of the chain
 							// cycle => resolve
 							cycle_pos = Some(target_pos);
-							MoveAction::Move(target_pos)
+							MoveAction::Move(target_pos, target_dir)
 						} else {
 							let contestants = self
 								.get_contestants(&source, target_pos, layer)
 								.iter()
-								.map(|pos| source[pos])
+								.map(|&pos| (pos, source.get(&pos).copied().unwrap()))
 								.collect::<Vec<_>>();
 
-							if contestants.is_empty() || self.luck_check(layer, &contestants, &ant)
+							if contestants.is_empty()
+								|| self.luck_check(layer, salt, &contestants, (pos, &ant))
 							{
 								// target is uncontested or conflict has been won => move
-								MoveAction::Move(target_pos)
+								MoveAction::Move(target_pos, target_dir)
 							} else {
 								// conflict has been lost => stay
-								MoveAction::Stay
+								MoveAction::Lost
 							}
 						}
 					}
 				} else {
 					// target pos is outside of grid
 					match self.border_mode(layer) {
-						BorderMode::Collide => MoveAction::Stay,
-						BorderMode::Despawn => MoveAction::Nop,
+						// reflecting only fails if the world is too small to turn around in
+						BorderMode::Collide | BorderMode::Reflect => MoveAction::Blocked,
+						BorderMode::Despawn => MoveAction::Despawn,
 						_ => panic!("no target position, despite border mode guaranteeing one"),
 					}
 				};
 
 				match action {
 					MoveAction::Stay => commit(&mut result, pos, ant),
-					MoveAction::Move(target_pos) => commit(&mut result, target_pos, ant),
+					MoveAction::Blocked => {
+						self.emit(layer, pos, &ant, EventKind::Blocked);
+						commit(&mut result, pos, ant)
+					}
+					MoveAction::Lost => {
+						self.emit(layer, pos, &ant, EventKind::Conflict);
+						commit(&mut result, pos, ant)
+					}
+					MoveAction::Move(target_pos, dir) => {
+						let energy = ant.energy.saturating_sub(move_cost);
+						let kind = EventKind::Move { from: pos };
+						self.emit(layer, target_pos, &ant, kind);
+						commit(&mut result, target_pos, Ant { dir, energy, ..ant })
+					}
+					MoveAction::Despawn => {
+						let kind = EventKind::Death {
+							cause: DeathCause::Despawn,
+						};
+						self.emit(layer, pos, &ant, kind);
+						self.count_deaths(1);
+					}
 					MoveAction::Nop => { /* ant will not be committed to result */ }
 				}
 			}
@@ -136,22 +228,32 @@ impl World {
 		}
 
 		fn commit(result: &mut Ants, pos: Pos, ant: Ant) {
-			let prev = result.insert(pos, ant);
+			let prev = result.append(pos, ant);
 			assert!(prev.is_none(), "tried to occupy occupied space")
 		}
 
-		swap(&mut result, self.ants.layer_mut(layer));
+		result.sort();
+		*self.ants.layer_mut(layer) = result;
+
+		source.clear();
+		self.scratch = source;
 	}
 
-	pub(super) fn spawn_tick(&mut self, source_layer: u8) {
+	/// spawns the children of the layer's ants.
+	///
+	/// children in a layer with `pending` updates this tick wait those out,
+	/// as well as the end of this update if they share its layer,
+	/// so no ant is updated on the tick it is born
+	pub(super) fn spawn_tick(&mut self, source_layer: u8, pending: &[u8]) {
 		let mut claims = BTreeMap::<(Pos, u8), Vec<Pos>>::new();
 
 		if self.ants.ant_count() >= self.config().ant_limit as usize {
 			return;
 		}
 
-		for (pos, ant) in &self.ants[&source_layer] {
-			if let Some(target_pos) = self.next_pos(*pos, source_layer, ant.dir.inverted())
+		for (pos, ant) in self.ants.layer(source_layer).iter() {
+			if let Some(target_pos) =
+				self.next_pos(pos, source_layer, self.config().lattice().invert(ant.dir))
 				&& ant.child_behavior != 0
 				&& !ant.waiting()
 				&& self.get_behavior(ant.child_behavior).is_some()
@@ -162,49 +264,93 @@ impl World {
 
 				let target_pos_occupied = self
 					.ants
-					.get(&target_layer)
+					.get(target_layer)
 					.is_some_and(|ants| ants.contains_key(&target_pos));
 
 				if target_layer_in_bounds && !target_pos_occupied {
 					claims
 						.entry((target_pos, target_layer))
 						.or_default()
-						.push(*pos);
+						.push(pos);
 				}
 			}
 		}
 
 		let mut new_ants: Vec<(Pos, u8, Ant)> = vec![];
+		let mut parents = vec![];
+
+		let salt = self.conflict_salt();
 
 		// resolve target position conflicts
 		for ((target_pos, target_layer), contestant_positions) in claims {
 			let contestants = contestant_positions
 				.iter()
-				.map(|pos| self.ants[&source_layer][pos])
+				.map(|&pos| {
+					(
+						pos,
+						self.ants.layer(source_layer).get(&pos).copied().unwrap(),
+					)
+				})
 				.collect::<Vec<_>>();
 
 			// conflict resolution
-			let ant = contestants
+			let (parent_pos, ant) = contestants
 				.iter()
-				.find(|ant| self.luck_check(target_layer, &contestants, ant))
+				.find(|(pos, ant)| self.luck_check(target_layer, salt, &contestants, (*pos, ant)))
 				.unwrap();
 
 			// spawn
-			let child_dir = ant.dir + ant.child_dir;
+			let pending_updates = pending
+				.iter()
+				.filter(|&&layer| layer == target_layer)
+				.count();
+			let wait_ticks = pending_updates as u8 + (target_layer == source_layer) as u8;
+
+			let child_dir = self.config().lattice().turn(ant.dir, ant.child_dir);
 
 			let new_ant = Ant {
 				behavior: ant.child_behavior,
 				memory: ant.child_memory,
 				dir: child_dir,
 				birth_tick: self.tick_count,
+				energy: self.config().energy.start_energy(ant.child_behavior),
+				serial: self.state.next_serial(),
+				parent: ant.serial,
+				wait_ticks,
 				..Default::default()
 			};
 
+			let kind = EventKind::Birth {
+				parent: *parent_pos,
+				parent_serial: ant.serial,
+			};
+			self.emit(target_layer, target_pos, &new_ant, kind);
+			self.count_birth(&new_ant);
+
+			for (pos, loser) in &contestants {
+				if pos != parent_pos {
+					self.emit(source_layer, *pos, loser, EventKind::Conflict);
+				}
+			}
+
 			new_ants.push((target_pos, target_layer, new_ant));
+			parents.push(*parent_pos);
 		}
 
-		for (pos, layer, ants) in new_ants.into_iter() {
-			self.ants.entry(layer).or_default().insert(pos, ants);
+		let spawn_cost = self.config().energy.spawn_cost as u16;
+
+		for pos in parents {
+			let parent = self.ants.layer_mut(source_layer).get_mut(&pos).unwrap();
+			parent.energy = parent.energy.saturating_sub(spawn_cost);
+		}
+
+		for layer in 0..self.config().layers {
+			let layer_ants = new_ants
+				.iter()
+				.filter(|(_, target_layer, _)| *target_layer == layer)
+				.map(|&(pos, _, ant)| (pos, ant));
+
+			self.ants.layer_mut(layer).extend(layer_ants);
 		}
 	}
 }
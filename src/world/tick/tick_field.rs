@@ -0,0 +1,98 @@
+use crate::{
+	util::{grid::Grid, vec2::Pos},
+	world::{World, state::FIELD_SCALE},
+};
+
+impl World {
+	/// spreads a share of each cell's field value evenly to its neighbors, then evaporates a share of it.
+	///
+	/// neighbors follow the border of layer 0, so field values can not cross
+	/// `collide` / `despawn` borders, but wrap around `cycle` / `wrap` borders
+	pub(super) fn field_tick(&mut self) {
+		let (diffusion, evaporation) = (self.config().diffusion, self.config().evaporation);
+		let (width, height) = (self.field.width, self.field.height);
+
+		let mut next: Grid<u32> = Grid::new(width, height);
+		let mut neighbors = vec![];
+
+		for (i, &value) in self.field.entries.iter().enumerate() {
+			if value == 0 {
+				continue;
+			}
+
+			let pos = Pos::from_index(i, width);
+			let value = value as u32;
+
+			neighbors.clear();
+
+			if diffusion > 0 {
+				neighbors.extend(
+					self.config()
+						.lattice()
+						.dirs()
+						.filter_map(|dir| self.next_pos(pos, 0, dir))
+						.filter(|&neighbor| neighbor != pos),
+				);
+			}
+
+			// the remainder of the even split stays in the cell, so no value is lost
+			let share = match neighbors.len() as u32 {
+				0 => 0,
+				count => value * diffusion / 0x100 / count,
+			};
+
+			for &neighbor in &neighbors {
+				next.set(neighbor, next.get(neighbor).unwrap() + share);
+			}
+
+			next.entries[i] += value - share * neighbors.len() as u32;
+		}
+
+		for (cell, value) in self.field.entries.iter_mut().zip(next.entries) {
+			// rounded up, so that any value evaporates eventually
+			let evaporated = (value * evaporation).div_ceil(0x100);
+			*cell = (value - evaporated).min(u16::MAX as u32) as u16;
+		}
+	}
+
+	/// the field value of a tile, as a byte
+	#[inline]
+	pub(super) fn field_level(&self, pos: Pos) -> u8 {
+		(*self.field.get(pos).unwrap() / FIELD_SCALE) as u8
+	}
+
+	#[inline]
+	pub(super) fn deposit(&mut self, pos: Pos, amount: u8) {
+		let value = self.field.get(pos).unwrap();
+		let new_value = value.saturating_add(amount as u16 * FIELD_SCALE);
+		self.field.set(pos, new_value);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{parser::compiler::compile_world_simple, world::World};
+
+	#[test]
+	fn diffusion_conserves_the_field() {
+		// a deposits once in the corner, while b keeps the world running
+		let code = "set size = 5; set border = collide; set diffusion = 200;
+			ant a = 1 { P7 = 1; Z = 1; } ant b = 2 { H = 1; }
+			spawn a { x = 0; y = 0; } spawn b { x = 4; y = 4; }";
+
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+		let total = |world: &World| world.field.entries.iter().map(|&value| value as u32).sum();
+
+		world.step_ticks(1);
+		let deposited: u32 = total(&world);
+		assert!(deposited > 0);
+
+		for _ in 0..50 {
+			world.step_ticks(1);
+			assert_eq!(total(&world), deposited);
+		}
+
+		// spread out by now
+		assert!(world.field.entries.iter().all(|&value| value > 0));
+	}
+}
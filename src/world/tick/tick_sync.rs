@@ -1,11 +1,14 @@
 use crate::{
 	ant::{
 		Ant,
+		input_plan::InputFetch,
 		pin::{Pin, PinValue},
-		sub_pin::SubPin,
 	},
 	util::{dir::Direction, vec2::Pos},
-	world::{World, config::BorderMode},
+	world::{
+		World,
+		config::{BorderMode, RandomMode},
+	},
 };
 
 fn zero_count_mask(x: u8) -> u8 {
@@ -14,33 +17,90 @@ fn zero_count_mask(x: u8) -> u8 {
 
 use Pin::*;
 impl World {
-	pub(super) fn get_input(&mut self, ant: &Ant, pos: Pos, layer: u8) -> u8 {
+	pub(super) fn get_input(&mut self, ant: &Ant, pos: Pos, layer: u8, step: u32) -> u8 {
+		// borrowing the behavior from the properties, so the RNG in the state can still be borrowed mutably
 		let behavior = self
-			.get_behavior(ant.behavior)
-			.cloned()
+			.properties
+			.behaviors
+			.get(&ant.behavior)
 			.expect("invalid Behavior ID");
 
-		let mut input_bits = 0u8;
+		let layer_plane = self.plane(layer, None);
 
-		for input_sub_pin in behavior.inputs.iter() {
-			let SubPin { pin, line, channel } = *input_sub_pin;
+		let mut input_bits = 0u8;
 
-			let target_dir = Direction::from(channel) + ant.dir;
-			let target_pos = self.next_pos(pos, layer, target_dir);
-			let target_ant = target_pos.and_then(|pos| self.ants[&layer].get(&pos));
+		// ants born since the layer's previous update haven't been updated yet
+		let last_update = match step {
+			0 => self
+				.tick_count
+				.saturating_sub(self.config().layer_period(layer)),
+			_ => self.tick_count,
+		};
+
+		let random_mode = self.config().random;
+		// random draws of this ant, keying the hashed random bits
+		let mut draws = 0;
+
+		for fetch in behavior.input_plan.fetches() {
+			let InputFetch {
+				pin,
+				channel,
+				plane,
+				layer: sensed_layer,
+				..
+			} = *fetch;
+
+			let tiles = &self.planes[plane.map_or(layer_plane, usize::from)];
+			let sensed_ants = self.ants.layer(sensed_layer.unwrap_or(layer));
+
+			let (target_pos, target_ant) = match pin {
+				NearbyTile | NearbyAnt | NearbyId | NearbyMem | NearbyField | Gradient => {
+					let target_dir = self
+						.config()
+						.lattice()
+						.turn(ant.dir, Direction::from(channel));
+					// borders are the ones of the ant's own layer, even if sensing another
+					let target_pos = self.next_pos(pos, layer, target_dir);
+					let target_ant = target_pos.and_then(|pos| sensed_ants.get(&pos));
+					(target_pos, target_ant)
+				}
+				LayerId | LayerMem => (Some(pos), sensed_ants.get(&pos)),
+				_ => (None, None),
+			};
 
 			let input_value: u8 = match pin {
-				Tile => *self.tiles.get(pos).unwrap(),
-				Clear => (*self.tiles.get(pos).unwrap() == 0) as u8,
+				Tile => *tiles.get(pos).unwrap(),
+				Clear => (*tiles.get(pos).unwrap() == 0) as u8,
 				NearbyTile => target_pos
-					.map(|pos| *self.tiles.get(pos).unwrap())
+					.map(|pos| *tiles.get(pos).unwrap())
 					.unwrap_or(0u8),
 
-				Init => (ant.birth_tick + 1 == self.tick_count()) as u8,
+				Field => self.field_level(pos),
+				NearbyField => target_pos
+					.map(|pos| self.field_level(pos))
+					.unwrap_or_default(),
+				Gradient => target_pos
+					.is_some_and(|target| self.field.get(target) > self.field.get(pos))
+					as u8,
+
+				Init => (ant.birth_tick >= last_update) as u8,
 				Time => ant.clock,
 				Pulse => zero_count_mask(ant.clock),
-				Random => self.rng(),
-				Chance => zero_count_mask(self.rng()),
+				Random | Chance => {
+					let random_bits = match random_mode {
+						RandomMode::Shared => self.state.rng(),
+						RandomMode::Hashed => {
+							draws += 1;
+							self.state.hashed_rng(layer, step, pos, draws)
+						}
+					};
+
+					if pin == Chance {
+						zero_count_mask(random_bits)
+					} else {
+						random_bits
+					}
+				}
 
 				NearbyAnt => {
 					(target_ant.is_some()
@@ -48,28 +108,30 @@ impl World {
 						as u8
 				}
 
-				NearbyId => target_ant.map(|target| target.behavior).unwrap_or_default(),
-				NearbyMem => target_ant.map(|target| target.memory).unwrap_or_default(),
+				NearbyId | LayerId => target_ant.map(|target| target.behavior).unwrap_or_default(),
+				NearbyMem | LayerMem => target_ant.map(|target| target.memory).unwrap_or_default(),
+
+				LayerAnt => self.ants.iter().fold(0, |bits, (layer, ants)| {
+					bits | ((ants.contains_key(&pos) as u8) << layer)
+				}),
 
 				Mem => ant.memory,
+				Energy => ant.energy.min(u8::MAX as u16) as u8,
 				Signal => self.signal_in,
 				ExtIn => self.ext_input,
 
-				_ => panic!("unhandled input: {input_sub_pin:?}"),
+				_ => panic!("unhandled input: {fetch:?}"),
 			};
 
-			let masked_input_value = (input_value >> line) & 1;
-			input_bits <<= 1;
-			input_bits |= masked_input_value;
+			input_bits |= fetch.extract(input_value);
 		}
 
 		input_bits
 	}
 
-	pub(super) fn get_output(&self, ant: &Ant, input: u8) -> Vec<PinValue> {
+	pub(super) fn get_output(&self, ant: &Ant, layer: u8, input: u8) -> Vec<PinValue> {
 		let behavior = self
 			.get_behavior(ant.behavior)
-			.cloned()
 			.expect("invalid Behavior ID");
 
 		// calculating the output
@@ -83,14 +145,20 @@ impl World {
 			let bit_index = (output_sub_pin.channel << 3) | output_sub_pin.line;
 			let new_value = output_bit << bit_index;
 
-			if let Some(output_value) = output_values
-				.iter_mut()
-				.find(|output_value| output_value.pin == output_sub_pin.pin)
-			{
+			// the layer's plane may also be addressed explicitly, so tiles are grouped by the plane they resolve to
+			let plane = match output_sub_pin.pin {
+				Tile | Clear => Some(self.plane(layer, output_sub_pin.plane) as u8),
+				_ => output_sub_pin.plane,
+			};
+
+			if let Some(output_value) = output_values.iter_mut().find(|output_value| {
+				(output_value.pin, output_value.plane) == (output_sub_pin.pin, plane)
+			}) {
 				output_value.value |= new_value;
 			} else {
 				output_values.push(PinValue {
 					pin: output_sub_pin.pin,
+					plane,
 					value: new_value,
 				});
 			}
@@ -102,20 +170,36 @@ impl World {
 	}
 
 	pub(super) fn sync_tick(&mut self, pos: Pos, layer: u8, input: u8, output: &[PinValue]) {
-		let mut ant = self.ants[&layer][&pos];
+		let mut ant = self.ants.layer(layer).get(&pos).copied().unwrap();
 
 		let behavior = self
 			.get_behavior(ant.behavior)
-			.cloned()
 			.expect("invalid Behavior ID");
 
-		let tile_mask = behavior.pin_mask(Pin::Tile);
-		let mem_mask = behavior.pin_mask(Pin::Mem);
-
-		let mut clear = false;
-
-		for pin_value in output.iter() {
-			let PinValue { pin, value } = *pin_value;
+		// tile masks of the planes each output value refers to, resolved like the output planes
+		let tile_masks = output
+			.iter()
+			.map(|pin_value| {
+				let plane = self.plane(layer, pin_value.plane);
+
+				(behavior.outputs.iter())
+					.filter(|sub_pin| {
+						sub_pin.pin == Tile && self.plane(layer, sub_pin.plane) == plane
+					})
+					.fold(0, |mask, sub_pin| mask | (1 << sub_pin.line))
+			})
+			.collect::<Vec<_>>();
+		let mem_mask = behavior.pin_mask(Pin::Mem, None);
+
+		// planes to clear, with the bits written this tick
+		let mut clears = vec![];
+
+		// whether any tile was changed, which costs energy
+		let mut wrote = false;
+
+		for (pin_value, tile_mask) in output.iter().zip(tile_masks) {
+			let PinValue { pin, plane, value } = *pin_value;
+			let plane = self.plane(layer, plane);
 			let value_bool = value != 0;
 			let wide_value = value;
 			let value = value as u8;
@@ -126,8 +210,11 @@ impl World {
 				(ExtOut, true) => self.ext_output.push(wide_value),
 
 				// tiles
-				(Clear, true) => clear = true,
-				(Tile, _) => self.set_tile(pos, value, tile_mask),
+				(Clear, true) => clears.push((plane, tile_mask)),
+				(Tile, _) => wrote |= self.set_tile(pos, plane, value, tile_mask),
+
+				// field
+				(Field, true) => self.deposit(pos, value),
 
 				// deferred to async ticks...
 
@@ -138,7 +225,12 @@ impl World {
 				(Halt, _) => ant.will_halt = value_bool,
 				(Dash, _) => ant.will_dash = value_bool,
 
-				(Dir, true) => ant.dir += Direction::from(value),
+				(Dir, true) => {
+					ant.dir = self
+						.config()
+						.lattice()
+						.turn(ant.dir, Direction::from(value))
+				}
 
 				// spawn_tick
 				(SpawnId, _) => ant.child_behavior = value,
@@ -162,10 +254,15 @@ impl World {
 		ant.last_input = input;
 		ant.clock = ant.clock.wrapping_add(1);
 
-		if clear {
-			self.set_tile(pos, 0, !tile_mask);
+		for (plane, tile_mask) in clears {
+			wrote |= self.set_tile(pos, plane, 0, !tile_mask);
+		}
+
+		if wrote {
+			let write_cost = self.config().energy.write_cost as u16;
+			ant.energy = ant.energy.saturating_sub(write_cost);
 		}
 
-		self.ants.get_mut(&layer).unwrap().insert(pos, ant);
+		*self.ants.layer_mut(layer).get_mut(&pos).unwrap() = ant;
 	}
 }
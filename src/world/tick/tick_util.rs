@@ -2,27 +2,45 @@ use crate::{
 	ant::Ant,
 	util::{
 		dir::Direction,
+		hash_u32,
 		vec2::{Pos, Vec2},
 	},
-	world::{World, config::BorderMode, state::Ants},
+	world::{
+		World,
+		ants::Ants,
+		config::{BorderMode, ConflictStrategy, Lattice, RandomMode},
+	},
 };
 
 impl World {
 	pub(super) fn next_pos(&self, pos: Pos, layer: u8, dir: Direction) -> Option<Pos> {
-		let new_pos = pos.sign() + dir.as_vec();
+		self.next_step(pos, layer, dir).map(|(pos, _)| pos)
+	}
 
-		if self.tiles.in_bounds(&new_pos) {
-			Some(new_pos.unsign().unwrap())
+	/// target position of a step in direction `dir`, along with the direction faced after the step
+	pub(super) fn next_step(
+		&self,
+		pos: Pos,
+		layer: u8,
+		dir: Direction,
+	) -> Option<(Pos, Direction)> {
+		let lattice = self.config().lattice();
+		let new_pos = lattice.neighbor(pos, dir);
+
+		// all planes share the dimensions of the world
+		let bounds = &self.planes[0];
+
+		if bounds.in_bounds(&new_pos) {
+			Some((new_pos.unsign().unwrap(), dir))
 		} else {
 			use BorderMode::*;
 
 			let border_mode = self.border_mode(layer);
+			let dimensions = bounds.dimensions().sign();
 
 			match border_mode {
 				Collide | Despawn => None,
 				Cycle | Wrap => {
-					let dimensions = self.tiles.dimensions().sign();
-
 					let mut wrapped_pos = new_pos % dimensions;
 
 					if let Wrap = border_mode {
@@ -30,12 +48,18 @@ impl World {
 						let (new_x, new_y) = (new_pos.x, new_pos.y);
 						let (mut wrapped_x, mut wrapped_y) = (wrapped_pos.x, wrapped_pos.y);
 
+						// odd hex rows are shifted, so steps wrap on to the next row of the same parity
+						let row_step = match lattice {
+							Lattice::Hex => 2,
+							Lattice::Moore | Lattice::VonNeumann => 1,
+						};
+
 						if new_x < 0 {
 							wrapped_x = size_x - 1;
-							wrapped_y = (wrapped_y - 1).rem_euclid(size_y);
+							wrapped_y = (wrapped_y - row_step).rem_euclid(size_y);
 						} else if new_x >= size_x {
 							wrapped_x = 0;
-							wrapped_y = (wrapped_y + 1).rem_euclid(size_y);
+							wrapped_y = (wrapped_y + row_step).rem_euclid(size_y);
 						}
 
 						if new_y < 0 {
@@ -52,43 +76,100 @@ impl World {
 						}
 					}
 
-					Some(wrapped_pos.unsign().unwrap())
+					Some((wrapped_pos.unsign().unwrap(), dir))
+				}
+				Klein => {
+					let mut wrapped_pos = new_pos % dimensions;
+					let mut dir = dir;
+
+					// crossing the left / right border flips the world upside down
+					if new_pos.x < 0 || new_pos.x >= dimensions.x {
+						wrapped_pos.y = dimensions.y - 1 - wrapped_pos.y;
+						dir = lattice.mirror(dir, false);
+					}
+
+					Some((wrapped_pos.unsign().unwrap(), dir))
+				}
+				Reflect => {
+					let mut dir = dir;
+					let mut new_pos = new_pos;
+
+					// mirror the direction on each axis the step would leave the world on
+					if new_pos.x < 0 || new_pos.x >= dimensions.x {
+						dir = lattice.mirror(dir, true);
+						new_pos = lattice.neighbor(pos, dir);
+					}
+
+					if new_pos.y < 0 || new_pos.y >= dimensions.y {
+						dir = lattice.mirror(dir, false);
+						new_pos = lattice.neighbor(pos, dir);
+					}
+
+					bounds
+						.in_bounds(&new_pos)
+						.then(|| (new_pos.unsign().unwrap(), dir))
 				}
 			}
 		}
 	}
 
-	pub(super) fn set_tile(&mut self, pos: Pos, value: u8, mask: u8) {
-		let old_value = self.tiles.get(pos).unwrap();
+	/// returns whether the tile's value changed
+	pub(super) fn set_tile(&mut self, pos: Pos, plane: usize, value: u8, mask: u8) -> bool {
+		let old_value = *self.planes[plane].get(pos).unwrap();
 		let new_value = value | (old_value & !mask);
-		self.set_value(pos, new_value);
+
+		if new_value != old_value && !self.config().watch.is_empty() {
+			self.check_tile_watches(plane, new_value);
+		}
+
+		self.set_value(pos, plane, new_value);
+
+		new_value != old_value
 	}
 
-	fn set_value(&mut self, pos: Pos, value: u8) {
+	fn set_value(&mut self, pos: Pos, plane: usize, value: u8) {
 		if let Some(decay) = self.config().decay {
 			if value != 0 {
-				let clock = self.tick_count as u16;
-				let expiration = clock.wrapping_add(decay);
-				self.tile_decays.insert(pos, expiration);
+				let tick = self.tick_count;
+				self.tile_decays[plane].schedule(pos, tick, decay);
 			} else {
-				self.tile_decays.remove(&pos);
+				self.tile_decays[plane].cancel(pos);
 			}
 		}
 
-		self.tiles.set(pos, value);
+		self.planes[plane].set(pos, value);
 	}
 
 	/// get positions of neighboring ants about to move to target
 	pub(super) fn get_contestants(&self, source: &Ants, target_pos: Pos, layer: u8) -> Vec<Pos> {
 		let mut positions = vec![];
 
-		for dir in 0..=Direction::MAX {
-			let dir = Direction::from(dir);
+		let lattice = self.config().lattice();
+
+		// klein and reflect borders bend or mirror steps, so every neighbor's actual step is checked.
+		// the other borders keep their original check, so existing worlds play out the same
+		let bending = matches!(
+			self.border_mode(layer),
+			BorderMode::Klein | BorderMode::Reflect
+		);
+
+		// whether the ant at `source_pos`, found in direction `dir`, steps onto the target
+		let steps_onto_target = |source_pos: Pos, source_ant: &Ant, dir: Direction| {
+			if bending {
+				self.next_pos(source_pos, layer, source_ant.dir) == Some(target_pos)
+			} else {
+				source_ant.dir == dir
+			}
+		};
 
-			if let Some(source_pos) = self.next_pos(target_pos, layer, dir.inverted())
+		for dir in lattice.dirs() {
+			let source_dir = if bending { dir } else { lattice.invert(dir) };
+
+			if let Some(source_pos) = self.next_pos(target_pos, layer, source_dir)
+				&& !positions.contains(&source_pos)
 				&& let Some(source_ant) = source.get(&source_pos)
 				&& !source_ant.halted()
-				&& source_ant.dir == dir
+				&& steps_onto_target(source_pos, source_ant, dir)
 			{
 				positions.push(source_pos);
 			}
@@ -97,16 +178,56 @@ impl World {
 		positions
 	}
 
-	pub(super) fn luck_check(&self, layer: u8, contestants: &[Ant], challenger: &Ant) -> bool {
-		if contestants.len() == 1 {
-			true
+	/// salt for the priorities of a conflict phase, only drawn from the RNG if needed
+	pub(super) fn conflict_salt(&mut self) -> u32 {
+		match (self.config().conflict, self.config().random) {
+			(ConflictStrategy::Random, RandomMode::Shared) => self.state.next_seed(),
+			(ConflictStrategy::Random, RandomMode::Hashed) => self.state.hashed_seed(&[]),
+			_ => 0,
+		}
+	}
+
+	/// whether the challenger wins against the contestants, which may include the challenger itself
+	pub(super) fn luck_check(
+		&self,
+		layer: u8,
+		salt: u32,
+		contestants: &[(Pos, Ant)],
+		challenger: (Pos, &Ant),
+	) -> bool {
+		let max_priority = contestants
+			.iter()
+			.map(|(pos, ant)| self.priority(layer, salt, *pos, ant))
+			.max()
+			.expect("luck check with no contestants");
+
+		let priority = self.priority(layer, salt, challenger.0, challenger.1);
+
+		if self.config().conflict == ConflictStrategy::Hash {
+			// kept as is, so existing worlds play out the same
+			contestants.len() == 1 || priority == max_priority
 		} else {
-			challenger.luck(self.tick_count, layer)
-				== contestants
-					.iter()
-					.map(|ant| ant.luck(self.tick_count, layer))
-					.max()
-					.expect("luck check with no contestants")
+			priority >= max_priority
 		}
 	}
+
+	fn priority(&self, layer: u8, salt: u32, pos: Pos, ant: &Ant) -> u64 {
+		use ConflictStrategy::*;
+
+		let dash = (ant.will_dash as u64) << 32;
+
+		let key = match self.config().conflict {
+			Hash => ant.luck(self.tick_count, layer) as u32,
+			Random => {
+				let pos_key = ((pos.y as u32) << 16) | pos.x as u32;
+				hash_u32(salt ^ hash_u32(pos_key ^ ((layer as u32) << 28)))
+			}
+			LowestId => (u8::MAX - ant.behavior) as u32,
+			HighestId => ant.behavior as u32,
+			Oldest => u32::MAX - ant.birth_tick,
+			Dash => 0,
+		};
+
+		dash | key as u64
+	}
 }
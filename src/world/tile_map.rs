@@ -0,0 +1,194 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{
+	util::{grid::Grid, vec2::Coord},
+	world::state::Tiles,
+};
+
+/// loads an initial tile grid, either from a PGM image (P2 / P5) or from a text map
+pub fn load_tiles(path: &Path) -> Result<Tiles> {
+	let bytes = fs::read(path)
+		.with_context(|| format!("error reading tile map '{}'!", path.to_string_lossy()))?;
+
+	let tiles = if bytes.starts_with(b"P2") || bytes.starts_with(b"P5") {
+		parse_pgm(&bytes)
+	} else {
+		String::from_utf8(bytes)
+			.map_err(|_| anyhow!("invalid UTF-8"))
+			.and_then(|text| parse_text_map(&text))
+	};
+
+	tiles.with_context(|| format!("invalid tile map '{}'!", path.to_string_lossy()))
+}
+
+/// one row per line, with either one hex digit per tile,
+/// or whitespace-separated hex numbers (e.g. `ff 0 1a`) for full bytes
+pub fn parse_text_map(text: &str) -> Result<Tiles> {
+	let mut width = None;
+	let mut entries = vec![];
+	let mut height = 0;
+
+	for (row, line) in text.lines().map(str::trim).enumerate() {
+		if line.is_empty() {
+			continue;
+		}
+
+		let cells = if line.contains(char::is_whitespace) {
+			line.split_whitespace()
+				.map(|cell| u8::from_str_radix(cell, 16).map_err(|_| cell.to_owned()))
+				.collect::<Vec<_>>()
+		} else {
+			line.chars()
+				.map(|cell| {
+					cell.to_digit(16)
+						.map(|digit| digit as u8)
+						.ok_or(cell.to_string())
+				})
+				.collect::<Vec<_>>()
+		};
+
+		let row_width = cells.len();
+
+		if *width.get_or_insert(row_width) != row_width {
+			bail!(
+				"row {} has {row_width} tiles, expected {}",
+				row + 1,
+				width.unwrap()
+			)
+		}
+
+		for cell in cells {
+			entries.push(cell.map_err(|cell| anyhow!("invalid tile '{cell}' in row {}", row + 1))?);
+		}
+
+		height += 1;
+	}
+
+	let width = width.ok_or(anyhow!("tile map is empty"))?;
+
+	grid(width, height, entries)
+}
+
+/// grayscale values are used as raw tile values, so `maxval` must not exceed 255
+pub fn parse_pgm(bytes: &[u8]) -> Result<Tiles> {
+	let mut reader = PgmReader { bytes, cursor: 0 };
+
+	let magic = reader.next_token()?;
+	let width = reader.next_number()?;
+	let height = reader.next_number()?;
+	let max_value = reader.next_number()?;
+
+	if max_value == 0 || max_value > u8::MAX as usize {
+		bail!("PGM maxval must be between 1 and 255, found {max_value}")
+	}
+
+	let len = width * height;
+
+	let entries = if magic == "P5" {
+		// exactly one whitespace byte separates the header from the binary data
+		let start = reader.cursor + 1;
+
+		bytes
+			.get(start..start + len)
+			.ok_or(anyhow!("PGM data is shorter than {width}x{height}"))?
+			.to_vec()
+	} else {
+		(0..len)
+			.map(|_| match reader.next_number()? {
+				value if value > max_value => bail!("PGM value {value} exceeds maxval"),
+				value => Ok(value as u8),
+			})
+			.collect::<Result<Vec<_>>>()?
+	};
+
+	grid(width, height, entries)
+}
+
+struct PgmReader<'a> {
+	bytes: &'a [u8],
+	cursor: usize,
+}
+
+impl PgmReader<'_> {
+	fn next_token(&mut self) -> Result<String> {
+		// skip whitespace and comments
+		loop {
+			match self.bytes.get(self.cursor) {
+				Some(byte) if byte.is_ascii_whitespace() => self.cursor += 1,
+				Some(b'#') => {
+					while self
+						.bytes
+						.get(self.cursor)
+						.is_some_and(|&byte| byte != b'\n')
+					{
+						self.cursor += 1;
+					}
+				}
+				_ => break,
+			}
+		}
+
+		let start = self.cursor;
+
+		while self
+			.bytes
+			.get(self.cursor)
+			.is_some_and(|byte| !byte.is_ascii_whitespace())
+		{
+			self.cursor += 1;
+		}
+
+		if start == self.cursor {
+			bail!("unexpected end of PGM data")
+		}
+
+		Ok(String::from_utf8_lossy(&self.bytes[start..self.cursor]).into_owned())
+	}
+
+	fn next_number(&mut self) -> Result<usize> {
+		let token = self.next_token()?;
+
+		token
+			.parse()
+			.map_err(|_| anyhow!("invalid number in PGM: '{token}'"))
+	}
+}
+
+fn grid(width: usize, height: usize, entries: Vec<u8>) -> Result<Tiles> {
+	if width > Coord::MAX as usize || height > Coord::MAX as usize {
+		bail!("tile map is too large: {width}x{height}")
+	}
+
+	Ok(Grid::with_entries(width as Coord, height as Coord, entries))
+}
+
+#[cfg(test)]
+mod test {
+	use super::{parse_pgm, parse_text_map};
+
+	#[test]
+	fn text_map() {
+		let tiles = parse_text_map("0f1\n a 0 ff \n\n").unwrap();
+		assert_eq!((tiles.width, tiles.height), (3, 2));
+		assert_eq!(tiles.entries, [0x0, 0xf, 0x1, 0xa, 0x0, 0xff]);
+
+		assert!(parse_text_map("00\n000").is_err());
+		assert!(parse_text_map("0g").is_err());
+		assert!(parse_text_map("").is_err());
+	}
+
+	#[test]
+	fn pgm() {
+		let tiles = parse_pgm(b"P2\n# comment\n2 2\n255\n0 1\n128 255\n").unwrap();
+		assert_eq!((tiles.width, tiles.height), (2, 2));
+		assert_eq!(tiles.entries, [0, 1, 128, 255]);
+
+		let tiles = parse_pgm(b"P5 3 1 255\n\x00\x0a\xff").unwrap();
+		assert_eq!(tiles.entries, [0, 10, 255]);
+
+		assert!(parse_pgm(b"P5 3 1 255\n\x00").is_err());
+		assert!(parse_pgm(b"P2 1 1 65535\n0").is_err());
+	}
+}
@@ -0,0 +1,270 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use anyhow::{Context, Error, anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::world::World;
+
+/// a condition that pauses or ends the simulation on the tick it becomes true
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Watch {
+	pub condition: WatchCondition,
+	pub action: WatchAction,
+}
+
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchCondition {
+	/// total ant count rises above this
+	AntsAbove(u32),
+	/// total ant count falls below this
+	AntsBelow(u32),
+	/// first ant with this behavior ID appears
+	Appear(u8),
+	/// last ant with this behavior ID disappears
+	Vanish(u8),
+	/// an ant sets a tile of the plane to the value
+	Tile { plane: u8, value: u8 },
+	/// an ant emits this value via `ExtOut`
+	ExtOut(u16),
+}
+
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchAction {
+	/// switch to stepped mode
+	Pause,
+	/// end the simulation
+	End,
+}
+
+/// parses conditions like `ants > 100`, `appear 2`, `vanish 2`, `tile ff`, `tile_1 ff` or `ext_out 3c`.
+/// tile and `ext_out` values are hexadecimal, like they are rendered
+impl TryFrom<String> for WatchCondition {
+	type Error = Error;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		let parts = value.split_whitespace().collect::<Vec<_>>();
+
+		let number = |part: &str, radix: u32| {
+			u32::from_str_radix(part, radix).with_context(|| format!("invalid number: '{part}'"))
+		};
+
+		let byte = |part: &str, radix: u32| {
+			number(part, radix)?
+				.try_into()
+				.map_err(|_| anyhow!("must not exceed 255: '{part}'"))
+		};
+
+		let condition = match parts.as_slice() {
+			["ants", ">", count] => Self::AntsAbove(number(count, 10)?),
+			["ants", "<", count] => Self::AntsBelow(number(count, 10)?),
+			["appear", id] => Self::Appear(byte(id, 10)?),
+			["vanish", id] => Self::Vanish(byte(id, 10)?),
+			["ext_out", value] => Self::ExtOut(
+				number(value, 16)?
+					.try_into()
+					.map_err(|_| anyhow!("must not exceed ffff: '{value}'"))?,
+			),
+			[tile, value] if tile.starts_with("tile") => {
+				let plane = match tile.strip_prefix("tile").unwrap() {
+					"" => 0,
+					suffix => suffix
+						.strip_prefix('_')
+						.and_then(|plane| plane.parse().ok())
+						.ok_or(anyhow!("invalid tile plane: '{tile}'"))?,
+				};
+
+				Self::Tile {
+					plane,
+					value: byte(value, 16)?,
+				}
+			}
+			_ => bail!("invalid watch condition: '{value}'"),
+		};
+
+		Ok(condition)
+	}
+}
+
+impl Display for WatchCondition {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::AntsAbove(count) => write!(f, "ants > {count}"),
+			Self::AntsBelow(count) => write!(f, "ants < {count}"),
+			Self::Appear(id) => write!(f, "appear {id}"),
+			Self::Vanish(id) => write!(f, "vanish {id}"),
+			Self::Tile { plane, value } => write!(f, "tile_{plane} {value:02x}"),
+			Self::ExtOut(value) => write!(f, "ext_out {value:02x}"),
+		}
+	}
+}
+
+/// a watch that fired, along with its index in the configured watches
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchEvent {
+	pub index: usize,
+	pub watch: Watch,
+}
+
+impl Display for WatchEvent {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let Self { index, watch } = self;
+
+		let action = match watch.action {
+			WatchAction::Pause => "pause",
+			WatchAction::End => "end",
+		};
+
+		write!(f, "watch #{index} fired: {} ({action})", watch.condition)
+	}
+}
+
+/// the observed values, compared before and after each tick
+#[derive(Debug, Clone, Default)]
+pub(super) struct WatchProbe {
+	ant_count: usize,
+	behaviors: BTreeSet<u8>,
+	ext_out_len: usize,
+}
+
+impl World {
+	pub(super) fn watch_probe(&self) -> WatchProbe {
+		WatchProbe {
+			ant_count: self.ants.ant_count(),
+			behaviors: self
+				.ants
+				.iter()
+				.flat_map(|(_, ants)| ants.iter().map(|(_, ant)| ant.behavior))
+				.collect(),
+			ext_out_len: self.ext_output.len(),
+		}
+	}
+
+	/// fires the first watch whose condition became true since `before` was probed
+	pub(super) fn check_watches(&mut self, before: &WatchProbe) {
+		if self.watch_event.is_some() {
+			return;
+		}
+
+		let after = self.watch_probe();
+		let ext_out = &self.ext_output[before.ext_out_len.min(self.ext_output.len())..];
+
+		let index = self.config().watch.iter().position(|watch| {
+			use WatchCondition::*;
+
+			match watch.condition {
+				AntsAbove(count) => {
+					before.ant_count <= count as usize && after.ant_count > count as usize
+				}
+				AntsBelow(count) => {
+					before.ant_count >= count as usize && after.ant_count < count as usize
+				}
+				Appear(id) => !before.behaviors.contains(&id) && after.behaviors.contains(&id),
+				Vanish(id) => before.behaviors.contains(&id) && !after.behaviors.contains(&id),
+				ExtOut(value) => ext_out.contains(&value),
+				// handled when the tile is set
+				Tile { .. } => false,
+			}
+		});
+
+		self.fire_watch(index);
+	}
+
+	/// fires the first watch on the tile value, if any
+	pub(super) fn check_tile_watches(&mut self, plane: usize, value: u8) {
+		if self.watch_event.is_some() {
+			return;
+		}
+
+		let index = self.config().watch.iter().position(|watch| {
+			watch.condition
+				== WatchCondition::Tile {
+					plane: plane as u8,
+					value,
+				}
+		});
+
+		self.fire_watch(index);
+	}
+
+	fn fire_watch(&mut self, index: Option<usize>) {
+		if let Some(index) = index {
+			let watch = self.config().watch[index].clone();
+			self.watch_event = Some(WatchEvent { index, watch });
+		}
+	}
+
+	/// the watch that fired during the latest frame
+	#[inline]
+	pub fn watch_event(&self) -> Option<&WatchEvent> {
+		self.watch_event.as_ref()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::WatchCondition::{self, *};
+	use crate::{parser::compiler::compile_world_simple, world::World};
+
+	#[test]
+	fn parse_conditions() {
+		let cases = [
+			("ants > 100", AntsAbove(100)),
+			("ants < 3", AntsBelow(3)),
+			("appear 2", Appear(2)),
+			("vanish 12", Vanish(12)),
+			(
+				"tile ff",
+				Tile {
+					plane: 0,
+					value: 0xff,
+				},
+			),
+			(
+				"tile_2 1a",
+				Tile {
+					plane: 2,
+					value: 0x1a,
+				},
+			),
+			("ext_out 3c", ExtOut(0x3c)),
+		];
+
+		for (text, condition) in cases {
+			let parsed = WatchCondition::try_from(text.to_owned()).unwrap();
+			assert_eq!(parsed, condition);
+			assert_eq!(
+				WatchCondition::try_from(parsed.to_string()).unwrap(),
+				condition
+			);
+		}
+
+		for invalid in ["ants = 1", "appear 256", "tile_x 1", "tile 100", "vanish"] {
+			assert!(WatchCondition::try_from(invalid.to_owned()).is_err());
+		}
+	}
+
+	#[test]
+	fn pause_keeps_the_config() {
+		let code = "set size = 8; set fps = 10; set pause_on = \"ants > 1\";
+			ant a = 1 { A0 = 1; D1 = 1; }";
+		let mut world = World::new(compile_world_simple(code).unwrap()).unwrap();
+
+		let frame = world.next_frame_auto().unwrap();
+		assert!(frame.watch_event.is_none());
+		assert_eq!(frame.ms, Some(100));
+
+		let frame = world.next_frame_auto().unwrap();
+		assert!(frame.watch_event.is_some());
+		assert_eq!(frame.ms, None);
+
+		// pausing is up to the runner
+		assert_eq!(world.config().fps, Some(10));
+		assert_eq!(world.next_frame_auto().unwrap().ms, Some(100));
+	}
+}